@@ -1,13 +1,12 @@
 use std::sync::Arc;
 
-use tokio::net::tcp::OwnedWriteHalf;
 use winit::{
     dpi::{PhysicalPosition, PhysicalSize},
     window::{Fullscreen, Window},
 };
 
 use game_core::player::MoveSpeed;
-use game_core::Vox;
+use game_core::{Session, Vox};
 use messages::ServerMessage; // ★ ServerMessage 임포트 (Debug 가능해졌음)
 
 use crate::surface_wrapper::SurfaceWrapper;
@@ -34,19 +33,19 @@ pub struct Context {
 }
 
 impl Context {
-    /// init: OwnedWriteHalf를 받아 Vox::init(...) 호출
+    /// init: Session을 받아 Vox::init(...) 호출
     pub fn init(
         config: &wgpu::SurfaceConfiguration,
         adapter: &wgpu::Adapter,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         window: Arc<Window>,
-        write_half: OwnedWriteHalf,
+        session: Session,
     ) -> Self {
         println!("\n[ CONTROL KEYS ]\nmovement: WASD + Shift + Space\nspeeding: CTRL\npause: ESC\nscreen mode: Tab");
 
         Context {
-            vox: Vox::init(config, adapter, device, queue, write_half),
+            vox: Vox::init(config, adapter, device, queue, session),
             window,
             input: FrameDrivenInput::new(),
             window_inner_position: PhysicalPosition::new(0, 0),
@@ -124,15 +123,19 @@ impl Context {
     /// 여기서 debug 출력이 가능해짐: `{:?}`
     pub fn handle_server_message(&mut self, msg: ServerMessage) {
         match msg {
-            ServerMessage::Init { your_player_id, your_position } => {
-                println!("Init => pid={}, position={:?}", your_player_id, your_position);
+            ServerMessage::Disconnect { reason } => {
+                eprintln!("Disconnected by server: {}", reason);
             }
-            ServerMessage::PlayerMove { moved_player_id, position } => {
-                println!("PlayerMove => pid={}, position={:?}", moved_player_id, position);
-            }
-            other => {
-                println!("Unhandled message: {:?}", other);
+            ServerMessage::PlayerMove {
+                moved_player_id,
+                position,
+            } => {
+                println!(
+                    "PlayerMove => pid={}, position={:?}",
+                    moved_player_id, position
+                );
             }
+            other => self.vox.handle_server_message(other),
         }
     }
 
@@ -1,5 +1,6 @@
 use std::{cell::RefCell, env, process::exit, rc::Rc, sync::Arc, time::Instant};
 
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{tcp::OwnedReadHalf, TcpStream};
 use tokio::sync::mpsc;
 use winit::{
@@ -18,7 +19,12 @@ use crate::context::Context;
 use crate::input::EventDrivenInput;
 use crate::surface_wrapper::SurfaceWrapper;
 use crate::wgpu_context::WGPUContext;
-use messages::ServerMessage;
+use game_core::{Session, CACHE_DISTANCE};
+use map_types::block;
+use messages::{
+    codec::{self, FrameDecoder},
+    ClientMessage, ServerMessage, PROTOCOL_VERSION,
+};
 
 struct EventLoopWrapper {
     event_loop: EventLoop<()>,
@@ -40,22 +46,31 @@ impl EventLoopWrapper {
 }
 
 /// 서버 메시지 수신 태스크 (읽기 전용)
-async fn network_listener(mut read_half: OwnedReadHalf, tx: mpsc::Sender<ServerMessage>) {
-    use tokio::io::AsyncReadExt;
-    let mut buffer = vec![0u8; 1024];
-    loop {
-        match read_half.read(&mut buffer).await {
+async fn network_listener(
+    mut read_half: OwnedReadHalf,
+    mut decoder: FrameDecoder,
+    tx: mpsc::Sender<ServerMessage>,
+) {
+    'connection: loop {
+        match read_half.read_buf(decoder.buffer_mut()).await {
             Ok(0) => {
                 println!("Server disconnected.");
                 break;
             }
-            Ok(n) => {
-                if let Ok(msg) = bincode::deserialize::<ServerMessage>(&buffer[..n]) {
-                    if tx.send(msg).await.is_err() {
-                        eprintln!("Failed to send server message to channel");
+            Ok(_) => loop {
+                match decoder.decode::<ServerMessage>() {
+                    Ok(Some(msg)) => {
+                        if tx.send(msg).await.is_err() {
+                            eprintln!("Failed to send server message to channel");
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        eprintln!("Invalid data from server: {}", e);
+                        break 'connection;
                     }
                 }
-            }
+            },
             Err(e) => {
                 eprintln!("Read error: {:?}", e);
                 break;
@@ -65,23 +80,88 @@ async fn network_listener(mut read_half: OwnedReadHalf, tx: mpsc::Sender<ServerM
     println!("network_listener finished.");
 }
 
+/// 서버에 Hello를 보내고 Init(수락) 또는 Disconnect(거절)를 기다림
+async fn handshake(
+    stream: &mut TcpStream,
+    decoder: &mut FrameDecoder,
+    player_name: String,
+) -> Result<(u32, u32, u64), String> {
+    let hello = codec::encode(&ClientMessage::Hello {
+        protocol_version: PROTOCOL_VERSION,
+        player_name,
+        requested_view_distance: CACHE_DISTANCE as u32,
+        blocks_fingerprint: block::registry().fingerprint(),
+    })
+    .map_err(|e| e.to_string())?;
+    stream.write_all(&hello).await.map_err(|e| e.to_string())?;
+
+    loop {
+        match decoder.decode::<ServerMessage>() {
+            Ok(Some(ServerMessage::Init {
+                your_player_id,
+                your_position,
+                view_distance,
+                seed,
+            })) => {
+                println!(
+                    "Init => pid={}, position={:?}, view_distance={}, seed={}",
+                    your_player_id, your_position, view_distance, seed
+                );
+                return Ok((your_player_id, view_distance, seed));
+            }
+            Ok(Some(ServerMessage::Disconnect { reason })) => return Err(reason.to_string()),
+            Ok(Some(other)) => return Err(format!("unexpected message {:?}", other)),
+            Ok(None) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+        match stream.read_buf(decoder.buffer_mut()).await {
+            Ok(0) => return Err("server closed the connection".to_string()),
+            Ok(_) => {}
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
     let server_addr = env::args().nth(1).unwrap_or_else(|| {
-        eprintln!("Usage: cargo run --release --bin client <ip>:<port>");
+        eprintln!("Usage: cargo run --release --bin client <ip>:<port> [player_name]");
         exit(0)
     });
+    let player_name = env::args()
+        .nth(2)
+        .or_else(|| env::var("USER").ok())
+        .unwrap_or_else(|| "player".to_string());
+    // 블록 정의 파일을 바꿨다면 서버도 같은 파일을 써야 접속됨
+    if let Ok(path) = env::var(block::DEFINITIONS_ENV) {
+        if let Err(e) = block::load_definitions(&path) {
+            eprintln!("{}: {}", path, e);
+            exit(1)
+        }
+    }
 
-    // 1) 서버 연결
-    let stream = TcpStream::connect(&server_addr).await.unwrap();
+    // 1) 서버 연결 + 핸드셰이크
+    let mut stream = TcpStream::connect(&server_addr).await.unwrap();
+    let mut decoder = FrameDecoder::new();
+    let (player_id, view_distance, seed) = handshake(&mut stream, &mut decoder, player_name)
+        .await
+        .unwrap_or_else(|reason| {
+            eprintln!("Connection refused: {}", reason);
+            exit(1)
+        });
     // 2) split -> read_half, write_half
     let (read_half, write_half) = stream.into_split();
-    // write_half를 Option으로 감싸서 한 번만 move하도록 함
-    let write_half_opt = Rc::new(RefCell::new(Some(write_half)));
+    // Session을 Option으로 감싸서 한 번만 move하도록 함
+    let session_opt = Rc::new(RefCell::new(Some(Session {
+        write_half,
+        player_id,
+        view_distance: view_distance as usize,
+        seed,
+    })));
 
     // 3) 백그라운드에서 read_half → network_listener
     let (tx, mut rx) = mpsc::channel::<ServerMessage>(100);
-    tokio::spawn(network_listener(read_half, tx));
+    tokio::spawn(network_listener(read_half, decoder, tx));
 
     // 4) winit & wgpu
     env_logger::init();
@@ -102,18 +182,18 @@ async fn main() {
                 ref e if SurfaceWrapper::start_condition(e) => {
                     surface.resume(&wgpu_context, window_loop.window.clone(), true);
                     if context.borrow().is_none() {
-                        // 7) Context::init(..., write_half) 한 번만 move하기 위해 Option에서 take()
-                        if let Some(wh) = write_half_opt.borrow_mut().take() {
+                        // 7) Context::init(..., session) 한 번만 move하기 위해 Option에서 take()
+                        if let Some(session) = session_opt.borrow_mut().take() {
                             *context.borrow_mut() = Some(Context::init(
                                 surface.config(),
                                 &wgpu_context.adapter,
                                 &wgpu_context.device,
                                 &wgpu_context.queue,
                                 window_loop.window.clone(),
-                                wh,
+                                session,
                             ));
                         } else {
-                            eprintln!("write_half already taken!");
+                            eprintln!("session already taken!");
                         }
                     }
                 }
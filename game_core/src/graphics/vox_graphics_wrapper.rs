@@ -3,7 +3,7 @@ use std::time::Instant;
 
 use glam::{vec2, Vec3};
 
-use map_types::CHUNK_SIZE;
+use map_types::{Dimension, CHUNK_SIZE};
 
 use crate::graphics::font_info::FontInfo;
 use crate::graphics::{SkyRenderer, UIRenderer, WorldRenderer};
@@ -34,6 +34,8 @@ pub struct VoxGraphicsWrapper {
     )>,
     last_frame_time: Instant,
     current_fps: u32,
+    world_seed: u64,
+    dimension: Dimension,
 }
 
 impl VoxGraphicsWrapper {
@@ -84,6 +86,8 @@ impl VoxGraphicsWrapper {
             text_meshes,
             last_frame_time: Instant::now(),
             current_fps: 0,
+            world_seed: 0,
+            dimension: Dimension::Overworld,
         }
     }
 
@@ -109,13 +113,29 @@ impl VoxGraphicsWrapper {
                 .create_text_mesh(device, text, vec2(10.0, 10.0), 0.8, &self.font_info);
     }
 
+    /// 정보 텍스트에 표시할 월드 시드
+    pub fn set_world_seed(&mut self, seed: u64) {
+        self.world_seed = seed;
+    }
+
+    /// 정보 텍스트에 표시할 현재 차원
+    pub fn set_dimension(&mut self, dimension: Dimension) {
+        self.dimension = dimension;
+    }
+
     pub fn update_info_text(&mut self, device: &wgpu::Device, fps: u32, triangle_count: u32) {
         let test_string = "abcdefghijklmnopqrstuvwxyz";
         let test_string_upper = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
         let test_string_number = "0123456789";
         let info_text = format!(
-            "FPS: {}\nTriangle: {}\n{}\n{}\n{}",
-            fps, triangle_count, test_string, test_string_upper, test_string_number
+            "FPS: {}\nTriangle: {}\nSeed: {}\n{}\n{}\n{}\n{}",
+            fps,
+            triangle_count,
+            self.world_seed,
+            self.dimension.name(),
+            test_string,
+            test_string_upper,
+            test_string_number
         );
         self.update_text(device, &info_text);
     }
@@ -8,8 +8,9 @@ use kira::{
     sound::static_sound::{StaticSoundData, StaticSoundHandle, StaticSoundSettings},
     AudioManager, AudioManagerSettings, DefaultBackend,
 };
-use messages::{ClientMessage, PlayerPosition, ServerMessage};
-use tokio::{io::AsyncWriteExt, net::tcp::OwnedWriteHalf};
+use map_types::Cube;
+use messages::{codec, ClientMessage, PlayerPosition, ServerMessage};
+use tokio::{io::AsyncWriteExt, net::tcp::OwnedWriteHalf, sync::mpsc};
 use wgpu::util::DeviceExt;
 
 mod graphics;
@@ -51,14 +52,8 @@ pub struct Server {
     /// 플레이어 ID(옵션)
     player_id: Arc<Mutex<Option<u32>>>,
 
-    /// 서버에 write하는 half
-    writer: Arc<tokio::sync::Mutex<OwnedWriteHalf>>,
-
-    /// 전송할 ClientMessage 버퍼
-    send_buffer: Arc<tokio::sync::Mutex<VecDeque<ClientMessage>>>,
-
-    /// 메시지 전송 태스크 중복 방지
-    is_sender_spawned: Arc<tokio::sync::Mutex<bool>>,
+    /// 전송할 ClientMessage 큐 (전송 태스크가 보낸 순서대로 write)
+    send_queue: mpsc::UnboundedSender<ClientMessage>,
 
     /// (주석) 원래는 서버에서 오는 read 메시지를 보관하던 큐
     receive_buffer: Arc<Mutex<VecDeque<ServerMessage>>>,
@@ -81,12 +76,21 @@ impl Server {
         //     }
         // });
 
-        // 이제는 writer만 보관
+        // 이제는 writer만 전송 태스크가 보관
+        let (send_queue, mut receiver) = mpsc::unbounded_channel::<ClientMessage>();
+        let mut writer = write_half;
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                let response_bytes = codec::encode(&message).unwrap();
+                if writer.write_all(&response_bytes).await.is_err() {
+                    break;
+                }
+            }
+        });
+
         let result = Server {
             player_id: Arc::new(Mutex::new(None)),
-            send_buffer: Arc::new(tokio::sync::Mutex::new(VecDeque::new())),
-            is_sender_spawned: Arc::new(tokio::sync::Mutex::new(false)),
-            writer: Arc::new(tokio::sync::Mutex::new(write_half)),
+            send_queue,
 
             // read loop 관련 필드들(사용 안함이지만, 한 줄도 생략하지 않는다는 요청으로 유지)
             receive_buffer: Arc::new(Mutex::new(VecDeque::new())),
@@ -98,38 +102,23 @@ impl Server {
         result
     }
 
-    /// 서버에 ClientMessage 전송
+    /// 서버에 ClientMessage 전송 (호출 순서 보장)
     fn send(&mut self, message: ClientMessage) {
-        let buffer = self.send_buffer.clone();
-        let is_sender_spawned = self.is_sender_spawned.clone();
-        let writer = self.writer.clone();
-
-        tokio::spawn(async move {
-            {
-                buffer.lock().await.push_back(message);
-            }
-
-            let mut sender_spawned_guard = is_sender_spawned.lock().await;
-            if !*sender_spawned_guard {
-                *sender_spawned_guard = true;
-                let is_sender_spawned = is_sender_spawned.clone();
-                tokio::spawn(async move {
-                    while let Some(message) = buffer.lock().await.pop_front() {
-                        let response_bytes = bincode::serialize(&message).unwrap();
-                        writer
-                            .lock()
-                            .await
-                            .write_all(&response_bytes)
-                            .await
-                            .unwrap();
-                    }
-                    *is_sender_spawned.lock().await = false;
-                });
-            }
-        });
+        // 전송 태스크가 끝났다면 연결이 끊긴 것이므로 무시
+        let _ = self.send_queue.send(message);
     }
 }
 
+/// 핸드셰이크가 끝난 서버 연결
+pub struct Session {
+    pub write_half: OwnedWriteHalf,
+    pub player_id: u32,
+    /// 서버가 허용한 시야 거리 (청크 단위)
+    pub view_distance: usize,
+    /// 서버 월드의 시드
+    pub seed: u64,
+}
+
 /// 게임 전체를 관리하는 Vox 구조체
 pub struct Vox {
     /// 서버(쓰기 전용)
@@ -147,6 +136,9 @@ pub struct Vox {
     /// 지형
     terrain_manager: TerrainManager,
 
+    /// 서버가 허용한 시야 거리 (청크 단위)
+    view_distance: usize,
+
     /// 포그 거리
     target_fog_distance: f32,
     current_fog_distance: f32,
@@ -157,18 +149,20 @@ pub struct Vox {
 }
 
 impl Vox {
-    /// 이제 `TcpStream` 대신 `OwnedWriteHalf`를 받도록 수정
+    /// 이제 `TcpStream` 대신 핸드셰이크가 끝난 `Session`을 받도록 수정
     pub fn init(
         config: &wgpu::SurfaceConfiguration,
         _adapter: &wgpu::Adapter,
         device: &wgpu::Device,
         queue: &wgpu::Queue,
-        /*stream: TcpStream*/ write_half: OwnedWriteHalf,
+        /*stream: TcpStream*/ session: Session,
     ) -> Self {
-        let vox_graphics_wrapper = VoxGraphicsWrapper::init(config, _adapter, device, queue);
+        let mut vox_graphics_wrapper = VoxGraphicsWrapper::init(config, _adapter, device, queue);
+        vox_graphics_wrapper.set_world_seed(session.seed);
 
         // Server::new(...)도 `write_half`만
-        let server = Arc::new(Mutex::new(Server::new(write_half)));
+        let server = Arc::new(Mutex::new(Server::new(session.write_half)));
+        *server.lock().unwrap().player_id.lock().unwrap() = Some(session.player_id);
 
         let eye_x = 0.0;
         let eye_y = -5.0;
@@ -188,7 +182,12 @@ impl Vox {
             vox_graphics_wrapper,
             local_player: Human::new(Vec3::new(eye_x, eye_y, eye_z)),
             is_paused: false,
-            terrain_manager: TerrainManager::new(CACHE_DISTANCE, (eye_x, eye_y), server.clone()),
+            terrain_manager: TerrainManager::new(
+                session.view_distance,
+                (eye_x, eye_y),
+                server.clone(),
+            ),
+            view_distance: session.view_distance,
             target_fog_distance: 0.0,
             current_fog_distance: 0.0,
             server,
@@ -214,6 +213,93 @@ impl Vox {
         self.vox_graphics_wrapper.resize(config, device, _queue);
     }
 
+    /// 지형 관련 서버 메시지 처리
+    pub fn handle_server_message(&mut self, msg: ServerMessage) {
+        match msg {
+            // 이전 차원에서 늦게 도착한 메시지는 버림
+            ServerMessage::Chunk { dimension, .. }
+            | ServerMessage::DestroyBlock { dimension, .. }
+            | ServerMessage::PutBlock { dimension, .. }
+            | ServerMessage::BlockChanges { dimension, .. }
+                if dimension != self.terrain_manager.dimension() => {}
+            ServerMessage::Chunk { x, y, chunk, .. } => {
+                self.terrain_manager.insert_chunk(x, y, *chunk);
+            }
+            ServerMessage::DestroyBlock {
+                chunk_x,
+                chunk_y,
+                block_x,
+                block_y,
+                block_z,
+                ..
+            } => {
+                self.terrain_manager.set_cube(
+                    chunk_x,
+                    chunk_y,
+                    block_x as usize,
+                    block_y as usize,
+                    block_z as usize,
+                    Cube::EMPTY,
+                );
+            }
+            ServerMessage::PutBlock {
+                chunk_x,
+                chunk_y,
+                block_x,
+                block_y,
+                block_z,
+                cube,
+                ..
+            } => {
+                self.terrain_manager.set_cube(
+                    chunk_x,
+                    chunk_y,
+                    block_x as usize,
+                    block_y as usize,
+                    block_z as usize,
+                    cube,
+                );
+            }
+            ServerMessage::BlockChanges {
+                chunk_x,
+                chunk_y,
+                changes,
+                ..
+            } => {
+                self.terrain_manager.set_cubes(
+                    chunk_x,
+                    chunk_y,
+                    changes.iter().map(|change| {
+                        (
+                            change.block_x as usize,
+                            change.block_y as usize,
+                            change.block_z as usize,
+                            change.cube,
+                        )
+                    }),
+                );
+            }
+            ServerMessage::Teleport {
+                position:
+                    PlayerPosition::InWorld {
+                        dimension,
+                        position,
+                        horizontal_rotation,
+                        vertical_rotation,
+                    },
+            } => {
+                self.local_player.position = Vec3::from(position);
+                self.local_player.horizontal_rotation = horizontal_rotation;
+                self.local_player.vertical_rotation = vertical_rotation;
+                self.terrain_manager.set_dimension(dimension);
+                self.vox_graphics_wrapper.set_dimension(dimension);
+            }
+            other => {
+                println!("Unhandled message: {:?}", other);
+            }
+        }
+    }
+
     /// 매 프레임 로직
     pub fn tick(
         &mut self,
@@ -244,6 +330,7 @@ impl Vox {
             let mut server_guard = self.server.lock().unwrap();
             server_guard.send(ClientMessage::Move {
                 position: PlayerPosition::InWorld {
+                    dimension: self.terrain_manager.dimension(),
                     position: [
                         self.local_player.position.x,
                         self.local_player.position.y,
@@ -270,9 +357,10 @@ impl Vox {
 
     /// 렌더링
     pub fn render(&mut self, view: &wgpu::TextureView, device: &wgpu::Device, queue: &wgpu::Queue) {
-        self.terrain_manager.set_cache_distance(CACHE_DISTANCE);
+        self.terrain_manager.set_cache_distance(self.view_distance);
         let eye_pos = self.local_player.get_eye_position();
         self.terrain_manager.set_eye((eye_pos.x, eye_pos.y));
+        self.terrain_manager.update_watching();
 
         let buffers = self.terrain_manager.get_available(&mut |mesh| {
             (
@@ -3,7 +3,8 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use map_types::{Chunk, CHUNK_SIZE};
+use map_types::{Chunk, Cube, Dimension, CHUNK_SIZE};
+use messages::ClientMessage;
 
 use crate::{
     get_coords,
@@ -20,6 +21,10 @@ pub struct TerrainManager {
     eye: (f32, f32),
     terrain_worker: TerrainWorker,
     server: Arc<Mutex<Server>>,
+    /// 보고 있는 차원
+    dimension: Dimension,
+    /// 서버에 WatchChunk를 보낸 청크들 (도착 여부와 무관)
+    watching: HashSet<(i32, i32)>,
 }
 
 pub struct Mesh {
@@ -28,7 +33,6 @@ pub struct Mesh {
 }
 
 struct MapCache {
-    pub chunk_loading: HashSet<(i32, i32)>,
     pub chunks: Vec<Option<Arc<Chunk>>>,
 
     pub cache_distance: usize,
@@ -45,7 +49,6 @@ impl MapCache {
         let (x, y) = eye;
 
         MapCache {
-            chunk_loading: HashSet::new(),
             chunks: vec![None; size * size],
             cache_distance,
             coords: calculate_coords(cache_distance as f32),
@@ -56,6 +59,14 @@ impl MapCache {
         }
     }
 
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let min_x = self.x - self.cache_distance as i32 - if self.eye_x_upper { 0 } else { 1 };
+        let max_x = self.x + self.cache_distance as i32 + if self.eye_x_upper { 1 } else { 0 };
+        let min_y = self.y - self.cache_distance as i32 - if self.eye_y_upper { 0 } else { 1 };
+        let max_y = self.y + self.cache_distance as i32 + if self.eye_y_upper { 1 } else { 0 };
+        min_x <= x && x <= max_x && min_y <= y && y <= max_y
+    }
+
     pub fn get(&self, x: i32, y: i32) -> Option<Arc<Chunk>> {
         let size = self.cache_distance * 2 + 2;
 
@@ -99,7 +110,6 @@ impl MapCache {
     fn reset(&mut self) {
         let size = self.cache_distance * 2 + 2;
         self.chunks = vec![None; size * size];
-        self.chunk_loading.clear();
     }
 }
 
@@ -235,10 +245,11 @@ impl TerrainManager {
             eye,
             terrain_worker: TerrainWorker::new(
                 Arc::new(Mutex::new(|| None)),
-                Arc::new(Mutex::new(|_pos, _chunk| ())),
                 Arc::new(Mutex::new(|_pos, _mesh| ())),
             ),
             server,
+            dimension: Dimension::Overworld,
+            watching: HashSet::new(),
         };
         result.init();
 
@@ -248,80 +259,18 @@ impl TerrainManager {
     fn init(&mut self) {
         self.terrain_worker = TerrainWorker::new(
             Arc::new(Mutex::new({
-                let map_cache = self.map_cache.clone();
                 let mesh_cache = self.mesh_cache.clone();
                 move || {
-                    let mut map_cache = map_cache.lock().unwrap();
-                    if let Some((position, vec)) =
-                        mesh_cache.lock().unwrap().mesh_load_request.pop_front()
-                    {
-                        return Some(TerrainWorkerJob::Mesh {
-                            position,
-                            zero: vec[0].clone(),
-                            positive_x: vec[1].clone(),
-                            negative_x: vec[2].clone(),
-                            positive_y: vec[3].clone(),
-                            negative_y: vec[4].clone(),
-                        });
-                    }
-                    let result = map_cache
-                        .coords
-                        .iter()
-                        .map(|&(x, y)| (x + map_cache.x, y + map_cache.y))
-                        .find(|&(x, y)| {
-                            map_cache.get(x, y).is_none()
-                                && !map_cache.chunk_loading.contains(&(x, y))
-                        });
-                    if let Some(pos) = result {
-                        map_cache.chunk_loading.insert(pos);
-                        return Some(TerrainWorkerJob::Map(pos));
-                    }
-                    None
-                }
-            })),
-            Arc::new(Mutex::new({
-                let map_cache = self.map_cache.clone();
-                let mesh_cache = self.mesh_cache.clone();
-                move |(x, y), chunk| {
-                    let mut map_cache = map_cache.lock().unwrap();
-
-                    map_cache.chunk_loading.remove(&(x, y));
-                    map_cache.set(x, y, Some(chunk));
-                    let directions2 = [
-                        (0, 0),  // itself
-                        (1, 0),  // x+1
-                        (-1, 0), // x-1
-                        (0, 1),  // y+1
-                        (0, -1), // y-1
-                    ];
-                    let directions = [
-                        (1, 0),  // x+1
-                        (-1, 0), // x-1
-                        (0, 1),  // y+1
-                        (0, -1), // y-1
-                    ];
-                    for (dx, dy) in directions2.iter() {
-                        if let Some(chunk) = map_cache.get(x + dx, y + dy) {
-                            let mut chunks5: Vec<Arc<Chunk>> = Vec::new();
-
-                            chunks5.push(chunk.clone());
-
-                            for (sub_dx, sub_dy) in directions.iter() {
-                                if let Some(sub_chunk) =
-                                    map_cache.get(x + dx + sub_dx, y + dy + sub_dy)
-                                {
-                                    chunks5.push(sub_chunk.clone());
-                                }
-                            }
-
-                            if chunks5.len() == 5 {
-                                let mut mesh_cache = mesh_cache.lock().unwrap();
-                                mesh_cache
-                                    .mesh_load_request
-                                    .push_back(((x + dx, y + dy), chunks5));
-                            }
-                        }
-                    }
+                    let (position, vec) =
+                        mesh_cache.lock().unwrap().mesh_load_request.pop_front()?;
+                    Some(TerrainWorkerJob::Mesh {
+                        position,
+                        zero: vec[0].clone(),
+                        positive_x: vec[1].clone(),
+                        negative_x: vec[2].clone(),
+                        positive_y: vec[3].clone(),
+                        negative_y: vec[4].clone(),
+                    })
                 }
             })),
             Arc::new(Mutex::new({
@@ -332,9 +281,156 @@ impl TerrainManager {
                 }
             })),
         );
+    }
 
-        // TODO: add proper watch/unwatch
-        // self.server.lock().unwrap().send(ClientMessage::WatchChunk { x: (), y: () });
+    /// 서버가 보낸 청크를 캐시에 넣고, 메시를 만들 수 있게 된 청크들을 요청
+    pub fn insert_chunk(&mut self, x: i32, y: i32, chunk: Chunk) {
+        if !self.watching.contains(&(x, y)) {
+            return;
+        }
+        let mut map_cache = self.map_cache.lock().unwrap();
+
+        map_cache.set(x, y, Some(Arc::new(chunk)));
+        self.request_meshes(&map_cache, x, y);
+    }
+
+    /// 서버가 알려준 블록 변경을 캐시된 청크에 반영하고 메시를 다시 요청
+    pub fn set_cube(
+        &mut self,
+        chunk_x: i32,
+        chunk_y: i32,
+        x: usize,
+        y: usize,
+        z: usize,
+        cube: Cube,
+    ) {
+        let mut map_cache = self.map_cache.lock().unwrap();
+        let Some(mut chunk) = map_cache.get(chunk_x, chunk_y) else {
+            return;
+        };
+        Arc::make_mut(&mut chunk).set(x, y, z, cube);
+        map_cache.set(chunk_x, chunk_y, Some(chunk));
+        self.request_meshes(&map_cache, chunk_x, chunk_y);
+    }
+
+    /// 한 청크의 여러 큐브를 바꾸고 메시는 한 번만 다시 만듦
+    pub fn set_cubes(
+        &mut self,
+        chunk_x: i32,
+        chunk_y: i32,
+        cubes: impl IntoIterator<Item = (usize, usize, usize, Cube)>,
+    ) {
+        let mut map_cache = self.map_cache.lock().unwrap();
+        let Some(mut chunk) = map_cache.get(chunk_x, chunk_y) else {
+            return;
+        };
+        let chunk_mut = Arc::make_mut(&mut chunk);
+        for (x, y, z, cube) in cubes {
+            chunk_mut.set(x, y, z, cube);
+        }
+        map_cache.set(chunk_x, chunk_y, Some(chunk));
+        self.request_meshes(&map_cache, chunk_x, chunk_y);
+    }
+
+    /// (x, y)와 그 이웃 중 5개 청크가 모두 있는 청크의 메시 생성을 요청
+    fn request_meshes(&self, map_cache: &MapCache, x: i32, y: i32) {
+        let directions2 = [
+            (0, 0),  // itself
+            (1, 0),  // x+1
+            (-1, 0), // x-1
+            (0, 1),  // y+1
+            (0, -1), // y-1
+        ];
+        let directions = [
+            (1, 0),  // x+1
+            (-1, 0), // x-1
+            (0, 1),  // y+1
+            (0, -1), // y-1
+        ];
+        for (dx, dy) in directions2.iter() {
+            if let Some(chunk) = map_cache.get(x + dx, y + dy) {
+                let mut chunks5: Vec<Arc<Chunk>> = Vec::new();
+
+                chunks5.push(chunk.clone());
+
+                for (sub_dx, sub_dy) in directions.iter() {
+                    if let Some(sub_chunk) = map_cache.get(x + dx + sub_dx, y + dy + sub_dy) {
+                        chunks5.push(sub_chunk.clone());
+                    }
+                }
+
+                if chunks5.len() == 5 {
+                    let mut mesh_cache = self.mesh_cache.lock().unwrap();
+                    mesh_cache
+                        .mesh_load_request
+                        .push_back(((x + dx, y + dy), chunks5));
+                }
+            }
+        }
+    }
+
+    /// 캐시 범위를 벗어난 청크는 unwatch, 새로 들어온 청크는 가까운 순서로 watch
+    pub fn update_watching(&mut self) {
+        let map_cache = self.map_cache.lock().unwrap();
+        let mut server = self.server.lock().unwrap();
+
+        self.watching.retain(|&(x, y)| {
+            let keep = map_cache.contains(x, y);
+            if !keep {
+                server.send(ClientMessage::UnwatchChunk {
+                    dimension: self.dimension,
+                    x,
+                    y,
+                });
+            }
+            keep
+        });
+        for (x, y) in map_cache
+            .coords
+            .iter()
+            .map(|&(x, y)| (x + map_cache.x, y + map_cache.y))
+        {
+            if self.watching.insert((x, y)) {
+                server.send(ClientMessage::WatchChunk {
+                    dimension: self.dimension,
+                    x,
+                    y,
+                });
+            }
+        }
+    }
+
+    /// 캐시가 통째로 비워졌으므로 받은 청크를 전부 다시 요청해야 함
+    fn unwatch_all(
+        server: &Mutex<Server>,
+        dimension: Dimension,
+        watching: &mut HashSet<(i32, i32)>,
+    ) {
+        let mut server = server.lock().unwrap();
+        for (x, y) in watching.drain() {
+            server.send(ClientMessage::UnwatchChunk { dimension, x, y });
+        }
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        self.dimension
+    }
+
+    /// 다른 차원으로 넘어가면 받아둔 청크를 모두 버리고 새 차원의 청크를 요청
+    pub fn set_dimension(&mut self, dimension: Dimension) {
+        if self.dimension == dimension {
+            return;
+        }
+        self.map_cache.lock().unwrap().reset();
+        self.buffer_cache.reset();
+        self.buffer_cache.farthest_distance_sq = 0;
+        {
+            let mut mesh_cache = self.mesh_cache.lock().unwrap();
+            mesh_cache.mesh_load_request.clear();
+            mesh_cache.meshes.clear();
+        }
+        Self::unwatch_all(&self.server, self.dimension, &mut self.watching);
+        self.dimension = dimension;
     }
 
     pub fn set_cache_distance(&mut self, new_cache_distance: usize) {
@@ -344,6 +440,7 @@ impl TerrainManager {
                 map_cache.cache_distance = new_cache_distance;
                 map_cache.coords = calculate_coords(map_cache.cache_distance as f32);
                 map_cache.reset();
+                Self::unwatch_all(&self.server, self.dimension, &mut self.watching);
             }
         }
 
@@ -420,6 +517,7 @@ impl TerrainManager {
             _ => {
                 map_cache.reset();
                 self.buffer_cache.reset();
+                Self::unwatch_all(&self.server, self.dimension, &mut self.watching);
                 return;
             }
         }
@@ -443,6 +541,7 @@ impl TerrainManager {
             _ => {
                 map_cache.reset();
                 self.buffer_cache.reset();
+                Self::unwatch_all(&self.server, self.dimension, &mut self.watching);
                 return;
             }
         }
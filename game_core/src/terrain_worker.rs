@@ -5,11 +5,9 @@ use std::{
 };
 
 use crate::{terrain_manager::Mesh, vertex::*};
-use map_core::Map;
 use map_types::Chunk;
 
 pub enum TerrainWorkerJob {
-    Map((i32, i32)),
     Mesh {
         position: (i32, i32),
         zero: Arc<Chunk>,
@@ -28,7 +26,6 @@ pub struct TerrainWorker {
 impl TerrainWorker {
     pub fn new(
         job_callback: Arc<Mutex<dyn Send + Sync + FnMut() -> Option<TerrainWorkerJob>>>,
-        chunk_callback: Arc<Mutex<dyn Send + Sync + FnMut((i32, i32), Arc<Chunk>)>>,
         mesh_callback: Arc<Mutex<dyn Send + Sync + FnMut((i32, i32), Mesh)>>,
     ) -> Self {
         let cpu_count = num_cpus::get_physical();
@@ -39,19 +36,13 @@ impl TerrainWorker {
         for _ in 0..worker_count {
             handles.push(thread::spawn({
                 let job_callback = job_callback.clone();
-                let chunk_callback = chunk_callback.clone();
                 let mesh_callback = mesh_callback.clone();
                 let running = running.clone();
                 move || {
-                    let map = Map::new(42);
                     while *running.lock().unwrap() {
                         let option = job_callback.lock().unwrap()();
                         if let Some(job) = option {
                             match job {
-                                TerrainWorkerJob::Map((x, y)) => {
-                                    let chunk = map.get_chunk(x, y);
-                                    chunk_callback.lock().unwrap()((x, y), Arc::new(chunk));
-                                }
                                 TerrainWorkerJob::Mesh {
                                     position: (x, y),
                                     zero,
@@ -1,11 +1,14 @@
 use bytemuck::{Pod, Zeroable};
 use map_types::{
-    Chunk, Cube, Custom, FilteredSolid, Harvestable, Plantlike, Solid, Translucent, CHUNK_SIZE,
-    MAP_HEIGHT,
+    Chunk, Cube, FluidLevel, RenderClass, Tiles, CHUNK_SIZE, MAP_HEIGHT, SECTION_COUNT,
+    SECTION_HEIGHT,
 };
 
 use crate::terrain_manager::Mesh;
 
+/// 물 타일에 덧씌우는 색 (알파는 섞는 비율)
+const WATER_COLOR: [f32; 4] = [0.2, 0.4, 0.9, 0.75];
+
 #[repr(C)]
 #[derive(Clone, Copy, Pod, Zeroable)]
 pub struct Vertex {
@@ -51,298 +54,323 @@ pub fn create_mesh_for_chunk(
     let mut index_data_for_opaque = Vec::<u16>::new();
     let mut vertex_data_for_translucent = Vec::<Vertex>::new();
     let mut index_data_for_translucent = Vec::<u16>::new();
-    for z in 0..MAP_HEIGHT {
-        for y in 0..CHUNK_SIZE {
-            for x in 0..CHUNK_SIZE {
-                let actual_x = x_offset + x as i32;
-                let actual_y = y_offset + y as i32;
-                let actual_z = z as i32;
-                match chunk.cubes[z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x] {
-                    Cube::Empty => {}
-                    Cube::Solid(solid) => {
-                        if vertex_data_for_opaque.len() > 60000 {
-                            opaque_buffers.push((vertex_data_for_opaque, index_data_for_opaque));
-                            vertex_data_for_opaque = Vec::new();
-                            index_data_for_opaque = Vec::new();
-                        }
-                        let (mut tmp_vertex_data, mut tmp_index_data) = create_vertices_for_solid(
-                            solid,
-                            actual_x as f32,
-                            actual_y as f32,
-                            actual_z as f32,
-                            if x == CHUNK_SIZE - 1 {
-                                chunk_px.cubes[z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE]
-                                    .is_solid()
-                            } else {
-                                chunk.cubes[z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x + 1]
-                                    .is_solid()
-                            },
-                            if x == 0 {
-                                chunk_nx.cubes
-                                    [z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + CHUNK_SIZE - 1]
-                                    .is_solid()
-                            } else {
-                                chunk.cubes[z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x - 1]
-                                    .is_solid()
-                            },
-                            if y == CHUNK_SIZE - 1 {
-                                chunk_py.cubes[z * CHUNK_SIZE * CHUNK_SIZE + x].is_solid()
-                            } else {
-                                chunk.cubes[z * CHUNK_SIZE * CHUNK_SIZE + (y + 1) * CHUNK_SIZE + x]
-                                    .is_solid()
-                            },
-                            if y == 0 {
-                                chunk_ny.cubes[z * CHUNK_SIZE * CHUNK_SIZE
-                                    + (CHUNK_SIZE - 1) * CHUNK_SIZE
-                                    + x]
-                                    .is_solid()
-                            } else {
-                                chunk.cubes[z * CHUNK_SIZE * CHUNK_SIZE + (y - 1) * CHUNK_SIZE + x]
-                                    .is_solid()
-                            },
-                            if z == MAP_HEIGHT - 1 {
-                                false
-                            } else {
-                                chunk.cubes[(z + 1) * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x]
-                                    .is_solid()
-                            },
-                            if z == 0 {
-                                false
-                            } else {
-                                chunk.cubes[(z - 1) * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x]
-                                    .is_solid()
-                            },
-                            vertex_data_for_opaque.len(),
-                        );
-                        vertex_data_for_opaque.append(&mut tmp_vertex_data);
-                        index_data_for_opaque.append(&mut tmp_index_data);
-                    }
-                    Cube::Translucent(translucent) => {
-                        if vertex_data_for_translucent.len() > 60000 {
-                            translucent_buffers
-                                .push((vertex_data_for_translucent, index_data_for_translucent));
-                            vertex_data_for_translucent = Vec::new();
-                            index_data_for_translucent = Vec::new();
-                        }
-                        let (mut tmp_vertex_data, mut tmp_index_data) =
-                            create_vertices_for_translucent(
-                                translucent,
-                                actual_x as f32,
-                                actual_y as f32,
-                                actual_z as f32,
-                                if x == CHUNK_SIZE - 1 {
-                                    chunk_px.cubes[z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE]
-                                        .is_translucent_or_solid()
-                                } else {
-                                    chunk.cubes
-                                        [z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x + 1]
-                                        .is_translucent_or_solid()
-                                },
-                                if x == 0 {
-                                    chunk_nx.cubes[z * CHUNK_SIZE * CHUNK_SIZE
-                                        + y * CHUNK_SIZE
-                                        + CHUNK_SIZE
-                                        - 1]
-                                    .is_translucent_or_solid()
-                                } else {
-                                    chunk.cubes
-                                        [z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x - 1]
-                                        .is_translucent_or_solid()
-                                },
-                                if y == CHUNK_SIZE - 1 {
-                                    chunk_py.cubes[z * CHUNK_SIZE * CHUNK_SIZE + x]
-                                        .is_translucent_or_solid()
-                                } else {
-                                    chunk.cubes
-                                        [z * CHUNK_SIZE * CHUNK_SIZE + (y + 1) * CHUNK_SIZE + x]
-                                        .is_translucent_or_solid()
-                                },
-                                if y == 0 {
-                                    chunk_ny.cubes[z * CHUNK_SIZE * CHUNK_SIZE
-                                        + (CHUNK_SIZE - 1) * CHUNK_SIZE
-                                        + x]
-                                        .is_translucent_or_solid()
-                                } else {
-                                    chunk.cubes
-                                        [z * CHUNK_SIZE * CHUNK_SIZE + (y - 1) * CHUNK_SIZE + x]
-                                        .is_translucent_or_solid()
-                                },
-                                if z == MAP_HEIGHT - 1 {
-                                    false
-                                } else {
-                                    chunk.cubes
-                                        [(z + 1) * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x]
-                                        .is_translucent_or_solid()
-                                },
-                                if z == 0 {
-                                    false
-                                } else {
-                                    chunk.cubes
-                                        [(z - 1) * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x]
-                                        .is_translucent_or_solid()
-                                },
-                                vertex_data_for_translucent.len(),
-                            );
-                        vertex_data_for_translucent.append(&mut tmp_vertex_data);
-                        index_data_for_translucent.append(&mut tmp_index_data);
-                    }
-                    Cube::FilteredSolid(filtered_solid) => {
-                        if vertex_data_for_opaque.len() > 60000 {
-                            opaque_buffers.push((vertex_data_for_opaque, index_data_for_opaque));
-                            vertex_data_for_opaque = Vec::new();
-                            index_data_for_opaque = Vec::new();
+    let neighbors = [chunk_px, chunk_nx, chunk_py, chunk_ny];
+    for (section_index, section) in chunk.sections().iter().enumerate() {
+        // 빈 섹션이나 사방이 막힌 섹션은 그릴 면이 없으므로 건너뜀
+        if section.is_empty() || is_hidden_section(chunk, neighbors, section_index) {
+            continue;
+        }
+        for z in section_index * SECTION_HEIGHT..(section_index + 1) * SECTION_HEIGHT {
+            for y in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let actual_x = x_offset + x as i32;
+                    let actual_y = y_offset + y as i32;
+                    let actual_z = z as i32;
+                    let block = chunk.get(x, y, z).block();
+                    // 보이지 않는 블록은 타일이 없음
+                    let Some(tiles) = &block.tiles else {
+                        continue;
+                    };
+                    match block.render {
+                        RenderClass::Invisible => {}
+                        RenderClass::Solid => {
+                            if vertex_data_for_opaque.len() > 60000 {
+                                opaque_buffers
+                                    .push((vertex_data_for_opaque, index_data_for_opaque));
+                                vertex_data_for_opaque = Vec::new();
+                                index_data_for_opaque = Vec::new();
+                            }
+                            let (mut tmp_vertex_data, mut tmp_index_data) =
+                                create_vertices_for_solid(
+                                    tiles,
+                                    actual_x as f32,
+                                    actual_y as f32,
+                                    actual_z as f32,
+                                    if x == CHUNK_SIZE - 1 {
+                                        chunk_px.get(0, y, z).is_solid()
+                                    } else {
+                                        chunk.get(x + 1, y, z).is_solid()
+                                    },
+                                    if x == 0 {
+                                        chunk_nx.get(CHUNK_SIZE - 1, y, z).is_solid()
+                                    } else {
+                                        chunk.get(x - 1, y, z).is_solid()
+                                    },
+                                    if y == CHUNK_SIZE - 1 {
+                                        chunk_py.get(x, 0, z).is_solid()
+                                    } else {
+                                        chunk.get(x, y + 1, z).is_solid()
+                                    },
+                                    if y == 0 {
+                                        chunk_ny.get(x, CHUNK_SIZE - 1, z).is_solid()
+                                    } else {
+                                        chunk.get(x, y - 1, z).is_solid()
+                                    },
+                                    if z == MAP_HEIGHT - 1 {
+                                        false
+                                    } else {
+                                        chunk.get(x, y, z + 1).is_solid()
+                                    },
+                                    if z == 0 {
+                                        false
+                                    } else {
+                                        chunk.get(x, y, z - 1).is_solid()
+                                    },
+                                    vertex_data_for_opaque.len(),
+                                );
+                            vertex_data_for_opaque.append(&mut tmp_vertex_data);
+                            index_data_for_opaque.append(&mut tmp_index_data);
                         }
-                        let (mut tmp_vertex_data, mut tmp_index_data) =
-                            create_vertices_for_filtered_solid(
-                                filtered_solid,
-                                actual_x as f32,
-                                actual_y as f32,
-                                actual_z as f32,
-                                if x == CHUNK_SIZE - 1 {
-                                    chunk_px.cubes[z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE]
-                                        .is_solid()
-                                } else {
-                                    chunk.cubes
-                                        [z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x + 1]
-                                        .is_solid()
-                                },
-                                if x == 0 {
-                                    chunk_nx.cubes[z * CHUNK_SIZE * CHUNK_SIZE
-                                        + y * CHUNK_SIZE
-                                        + CHUNK_SIZE
-                                        - 1]
-                                    .is_solid()
-                                } else {
-                                    chunk.cubes
-                                        [z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x - 1]
-                                        .is_solid()
-                                },
-                                if y == CHUNK_SIZE - 1 {
-                                    chunk_py.cubes[z * CHUNK_SIZE * CHUNK_SIZE + x].is_solid()
-                                } else {
-                                    chunk.cubes
-                                        [z * CHUNK_SIZE * CHUNK_SIZE + (y + 1) * CHUNK_SIZE + x]
-                                        .is_solid()
-                                },
-                                if y == 0 {
-                                    chunk_ny.cubes[z * CHUNK_SIZE * CHUNK_SIZE
-                                        + (CHUNK_SIZE - 1) * CHUNK_SIZE
-                                        + x]
-                                        .is_solid()
-                                } else {
-                                    chunk.cubes
-                                        [z * CHUNK_SIZE * CHUNK_SIZE + (y - 1) * CHUNK_SIZE + x]
-                                        .is_solid()
-                                },
-                                if z == MAP_HEIGHT - 1 {
-                                    false
-                                } else {
-                                    chunk.cubes
-                                        [(z + 1) * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x]
-                                        .is_solid()
-                                },
-                                if z == 0 {
-                                    false
-                                } else {
-                                    chunk.cubes
-                                        [(z - 1) * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x]
-                                        .is_solid()
-                                },
-                                [
-                                    chunk.biome_colors[y * CHUNK_SIZE + x],
+                        RenderClass::Translucent => {
+                            if vertex_data_for_translucent.len() > 60000 {
+                                translucent_buffers.push((
+                                    vertex_data_for_translucent,
+                                    index_data_for_translucent,
+                                ));
+                                vertex_data_for_translucent = Vec::new();
+                                index_data_for_translucent = Vec::new();
+                            }
+                            let (mut tmp_vertex_data, mut tmp_index_data) =
+                                create_vertices_for_translucent(
+                                    tiles,
+                                    actual_x as f32,
+                                    actual_y as f32,
+                                    actual_z as f32,
                                     if x == CHUNK_SIZE - 1 {
-                                        chunk_px.biome_colors[y * CHUNK_SIZE]
+                                        chunk_px.get(0, y, z).is_translucent_or_solid()
+                                    } else {
+                                        chunk.get(x + 1, y, z).is_translucent_or_solid()
+                                    },
+                                    if x == 0 {
+                                        chunk_nx.get(CHUNK_SIZE - 1, y, z).is_translucent_or_solid()
                                     } else {
-                                        chunk.biome_colors[y * CHUNK_SIZE + x + 1]
+                                        chunk.get(x - 1, y, z).is_translucent_or_solid()
                                     },
                                     if y == CHUNK_SIZE - 1 {
-                                        chunk_py.biome_colors[x]
+                                        chunk_py.get(x, 0, z).is_translucent_or_solid()
+                                    } else {
+                                        chunk.get(x, y + 1, z).is_translucent_or_solid()
+                                    },
+                                    if y == 0 {
+                                        chunk_ny.get(x, CHUNK_SIZE - 1, z).is_translucent_or_solid()
+                                    } else {
+                                        chunk.get(x, y - 1, z).is_translucent_or_solid()
+                                    },
+                                    if z == MAP_HEIGHT - 1 {
+                                        false
+                                    } else {
+                                        chunk.get(x, y, z + 1).is_translucent_or_solid()
+                                    },
+                                    if z == 0 {
+                                        false
                                     } else {
-                                        chunk.biome_colors[(y + 1) * CHUNK_SIZE + x]
+                                        chunk.get(x, y, z - 1).is_translucent_or_solid()
+                                    },
+                                    vertex_data_for_translucent.len(),
+                                );
+                            vertex_data_for_translucent.append(&mut tmp_vertex_data);
+                            index_data_for_translucent.append(&mut tmp_index_data);
+                        }
+                        RenderClass::FilteredSolid => {
+                            if vertex_data_for_opaque.len() > 60000 {
+                                opaque_buffers
+                                    .push((vertex_data_for_opaque, index_data_for_opaque));
+                                vertex_data_for_opaque = Vec::new();
+                                index_data_for_opaque = Vec::new();
+                            }
+                            let (mut tmp_vertex_data, mut tmp_index_data) =
+                                create_vertices_for_filtered_solid(
+                                    tiles,
+                                    block
+                                        .filter_tiles
+                                        .as_ref()
+                                        .expect("checked when the definitions are loaded"),
+                                    actual_x as f32,
+                                    actual_y as f32,
+                                    actual_z as f32,
+                                    if x == CHUNK_SIZE - 1 {
+                                        chunk_px.get(0, y, z).is_solid()
+                                    } else {
+                                        chunk.get(x + 1, y, z).is_solid()
+                                    },
+                                    if x == 0 {
+                                        chunk_nx.get(CHUNK_SIZE - 1, y, z).is_solid()
+                                    } else {
+                                        chunk.get(x - 1, y, z).is_solid()
                                     },
                                     if y == CHUNK_SIZE - 1 {
-                                        if x == CHUNK_SIZE - 1 {
-                                            [0.0, 0.0, 0.0, 0.0] // TODO: fix
-                                        } else {
-                                            chunk_py.biome_colors[x + 1]
-                                        }
-                                    } else if x == CHUNK_SIZE - 1 {
-                                        chunk_px.biome_colors[(y + 1) * CHUNK_SIZE]
+                                        chunk_py.get(x, 0, z).is_solid()
                                     } else {
-                                        chunk.biome_colors[(y + 1) * CHUNK_SIZE + x + 1]
+                                        chunk.get(x, y + 1, z).is_solid()
                                     },
-                                ],
-                                vertex_data_for_opaque.len(),
-                            );
-                        vertex_data_for_opaque.append(&mut tmp_vertex_data);
-                        index_data_for_opaque.append(&mut tmp_index_data);
-                    }
-                    Cube::Plantlike(plantlike) => {
-                        if vertex_data_for_translucent.len() > 60000 {
-                            translucent_buffers
-                                .push((vertex_data_for_translucent, index_data_for_translucent));
-                            vertex_data_for_translucent = Vec::new();
-                            index_data_for_translucent = Vec::new();
+                                    if y == 0 {
+                                        chunk_ny.get(x, CHUNK_SIZE - 1, z).is_solid()
+                                    } else {
+                                        chunk.get(x, y - 1, z).is_solid()
+                                    },
+                                    if z == MAP_HEIGHT - 1 {
+                                        false
+                                    } else {
+                                        chunk.get(x, y, z + 1).is_solid()
+                                    },
+                                    if z == 0 {
+                                        false
+                                    } else {
+                                        chunk.get(x, y, z - 1).is_solid()
+                                    },
+                                    [
+                                        chunk.biome_colors[y * CHUNK_SIZE + x],
+                                        if x == CHUNK_SIZE - 1 {
+                                            chunk_px.biome_colors[y * CHUNK_SIZE]
+                                        } else {
+                                            chunk.biome_colors[y * CHUNK_SIZE + x + 1]
+                                        },
+                                        if y == CHUNK_SIZE - 1 {
+                                            chunk_py.biome_colors[x]
+                                        } else {
+                                            chunk.biome_colors[(y + 1) * CHUNK_SIZE + x]
+                                        },
+                                        if y == CHUNK_SIZE - 1 {
+                                            if x == CHUNK_SIZE - 1 {
+                                                [0.0, 0.0, 0.0, 0.0] // TODO: fix
+                                            } else {
+                                                chunk_py.biome_colors[x + 1]
+                                            }
+                                        } else if x == CHUNK_SIZE - 1 {
+                                            chunk_px.biome_colors[(y + 1) * CHUNK_SIZE]
+                                        } else {
+                                            chunk.biome_colors[(y + 1) * CHUNK_SIZE + x + 1]
+                                        },
+                                    ],
+                                    vertex_data_for_opaque.len(),
+                                );
+                            vertex_data_for_opaque.append(&mut tmp_vertex_data);
+                            index_data_for_opaque.append(&mut tmp_index_data);
                         }
-                        let (mut tmp_vertex_data, mut tmp_index_data) =
-                            create_vertices_for_plantlike(
-                                plantlike,
-                                actual_x as f32,
-                                actual_y as f32,
-                                actual_z as f32,
-                                vertex_data_for_translucent.len(),
-                            );
-                        vertex_data_for_translucent.append(&mut tmp_vertex_data);
-                        index_data_for_translucent.append(&mut tmp_index_data);
-                    }
-                    Cube::Harvestable(harvestable) => {
-                        if vertex_data_for_translucent.len() > 60000 {
-                            translucent_buffers
-                                .push((vertex_data_for_translucent, index_data_for_translucent));
-                            vertex_data_for_translucent = Vec::new();
-                            index_data_for_translucent = Vec::new();
+                        RenderClass::Plantlike => {
+                            if vertex_data_for_translucent.len() > 60000 {
+                                translucent_buffers.push((
+                                    vertex_data_for_translucent,
+                                    index_data_for_translucent,
+                                ));
+                                vertex_data_for_translucent = Vec::new();
+                                index_data_for_translucent = Vec::new();
+                            }
+                            let (mut tmp_vertex_data, mut tmp_index_data) =
+                                create_vertices_for_plantlike(
+                                    tiles,
+                                    actual_x as f32,
+                                    actual_y as f32,
+                                    actual_z as f32,
+                                    vertex_data_for_translucent.len(),
+                                );
+                            vertex_data_for_translucent.append(&mut tmp_vertex_data);
+                            index_data_for_translucent.append(&mut tmp_index_data);
+                        }
+                        RenderClass::Harvestable => {
+                            if vertex_data_for_translucent.len() > 60000 {
+                                translucent_buffers.push((
+                                    vertex_data_for_translucent,
+                                    index_data_for_translucent,
+                                ));
+                                vertex_data_for_translucent = Vec::new();
+                                index_data_for_translucent = Vec::new();
+                            }
+                            let (mut tmp_vertex_data, mut tmp_index_data) =
+                                create_vertices_for_harvestable(
+                                    tiles,
+                                    actual_x as f32,
+                                    actual_y as f32,
+                                    actual_z as f32,
+                                    vertex_data_for_translucent.len(),
+                                );
+                            vertex_data_for_translucent.append(&mut tmp_vertex_data);
+                            index_data_for_translucent.append(&mut tmp_index_data);
                         }
-                        let (mut tmp_vertex_data, mut tmp_index_data) =
-                            create_vertices_for_harvestable(
-                                harvestable,
+                        RenderClass::Fluid => {
+                            if vertex_data_for_translucent.len() > 60000 {
+                                translucent_buffers.push((
+                                    vertex_data_for_translucent,
+                                    index_data_for_translucent,
+                                ));
+                                vertex_data_for_translucent = Vec::new();
+                                index_data_for_translucent = Vec::new();
+                            }
+                            let cover = |side: usize| {
+                                fluid_side_cover(
+                                    side_cube(chunk, neighbors, x, y, z, side),
+                                    side_cube(chunk, neighbors, x, y, z + 1, side),
+                                )
+                            };
+                            let up = if z == MAP_HEIGHT - 1 {
+                                Cube::EMPTY
+                            } else {
+                                chunk.get(x, y, z + 1)
+                            };
+                            let down = if z == 0 {
+                                Cube::EMPTY
+                            } else {
+                                chunk.get(x, y, z - 1)
+                            };
+                            let (mut tmp_vertex_data, mut tmp_index_data) =
+                                create_vertices_for_fluid(
+                                    tiles,
+                                    block
+                                        .filter_tiles
+                                        .as_ref()
+                                        .expect("checked when the definitions are loaded"),
+                                    actual_x as f32,
+                                    actual_y as f32,
+                                    actual_z as f32,
+                                    fluid_surface(
+                                        block
+                                            .fluid
+                                            .expect("checked when the definitions are loaded"),
+                                        up,
+                                    ),
+                                    [cover(0), cover(1), cover(2), cover(3)],
+                                    up.fluid().is_none(),
+                                    z != 0 && down.fluid().is_none() && !down.is_solid(),
+                                    vertex_data_for_translucent.len(),
+                                );
+                            vertex_data_for_translucent.append(&mut tmp_vertex_data);
+                            index_data_for_translucent.append(&mut tmp_index_data);
+                        }
+                        RenderClass::Cactus => {
+                            if vertex_data_for_opaque.len() > 60000 {
+                                opaque_buffers
+                                    .push((vertex_data_for_opaque, index_data_for_opaque));
+                                vertex_data_for_opaque = Vec::new();
+                                index_data_for_opaque = Vec::new();
+                            }
+                            if vertex_data_for_translucent.len() > 60000 {
+                                translucent_buffers.push((
+                                    vertex_data_for_translucent,
+                                    index_data_for_translucent,
+                                ));
+                                vertex_data_for_translucent = Vec::new();
+                                index_data_for_translucent = Vec::new();
+                            }
+                            let (
+                                (mut tmp_vertex_data_for_opaque, mut tmp_index_data_for_opaque),
+                                (
+                                    mut tmp_vertex_data_for_translucent,
+                                    mut tmp_index_data_for_translucent,
+                                ),
+                            ) = create_vertices_for_cactus(
+                                tiles,
                                 actual_x as f32,
                                 actual_y as f32,
                                 actual_z as f32,
+                                vertex_data_for_opaque.len(),
                                 vertex_data_for_translucent.len(),
                             );
-                        vertex_data_for_translucent.append(&mut tmp_vertex_data);
-                        index_data_for_translucent.append(&mut tmp_index_data);
-                    }
-                    Cube::Custom(custom) => {
-                        if vertex_data_for_opaque.len() > 60000 {
-                            opaque_buffers.push((vertex_data_for_opaque, index_data_for_opaque));
-                            vertex_data_for_opaque = Vec::new();
-                            index_data_for_opaque = Vec::new();
-                        }
-                        if vertex_data_for_translucent.len() > 60000 {
-                            translucent_buffers
-                                .push((vertex_data_for_translucent, index_data_for_translucent));
-                            vertex_data_for_translucent = Vec::new();
-                            index_data_for_translucent = Vec::new();
+                            vertex_data_for_opaque.append(&mut tmp_vertex_data_for_opaque);
+                            index_data_for_opaque.append(&mut tmp_index_data_for_opaque);
+                            vertex_data_for_translucent
+                                .append(&mut tmp_vertex_data_for_translucent);
+                            index_data_for_translucent.append(&mut tmp_index_data_for_translucent);
                         }
-                        let (
-                            (mut tmp_vertex_data_for_opaque, mut tmp_index_data_for_opaque),
-                            (
-                                mut tmp_vertex_data_for_translucent,
-                                mut tmp_index_data_for_translucent,
-                            ),
-                        ) = create_vertices_for_custom(
-                            custom,
-                            actual_x as f32,
-                            actual_y as f32,
-                            actual_z as f32,
-                            vertex_data_for_opaque.len(),
-                            vertex_data_for_translucent.len(),
-                        );
-                        vertex_data_for_opaque.append(&mut tmp_vertex_data_for_opaque);
-                        index_data_for_opaque.append(&mut tmp_index_data_for_opaque);
-                        vertex_data_for_translucent.append(&mut tmp_vertex_data_for_translucent);
-                        index_data_for_translucent.append(&mut tmp_index_data_for_translucent);
                     }
                 }
             }
@@ -360,8 +388,68 @@ pub fn create_mesh_for_chunk(
     }
 }
 
+/// 섹션과 위/아래 섹션, 옆 청크의 같은 높이 섹션이 모두 solid로 꽉 차 있으면 보이는 면이 없음
+/// (맨 아래/맨 위 섹션은 맵 경계 면을 그리므로 제외)
+fn is_hidden_section(chunk: &Chunk, neighbors: [&Chunk; 4], index: usize) -> bool {
+    if index == 0 || index == SECTION_COUNT - 1 {
+        return false;
+    }
+    let sections = chunk.sections();
+    sections[index - 1..=index + 1]
+        .iter()
+        .all(|section| section.is_full_solid())
+        && neighbors
+            .iter()
+            .all(|neighbor| neighbor.sections()[index].is_full_solid())
+}
+
+/// 같은 높이에서 옆 칸의 큐브. `side`는 `neighbors`와 같은 순서 (px, nx, py, ny)
+/// 이고, 청크 경계를 넘으면 이웃 청크에서 가져옴
+fn side_cube(
+    chunk: &Chunk,
+    neighbors: [&Chunk; 4],
+    x: usize,
+    y: usize,
+    z: usize,
+    side: usize,
+) -> Cube {
+    if z >= MAP_HEIGHT {
+        return Cube::EMPTY;
+    }
+    match side {
+        0 if x == CHUNK_SIZE - 1 => neighbors[0].get(0, y, z),
+        0 => chunk.get(x + 1, y, z),
+        1 if x == 0 => neighbors[1].get(CHUNK_SIZE - 1, y, z),
+        1 => chunk.get(x - 1, y, z),
+        2 if y == CHUNK_SIZE - 1 => neighbors[2].get(x, 0, z),
+        2 => chunk.get(x, y + 1, z),
+        _ if y == 0 => neighbors[3].get(x, CHUNK_SIZE - 1, z),
+        _ => chunk.get(x, y - 1, z),
+    }
+}
+
+/// 유체 표면 높이. 위에 유체가 있으면 이어지도록 칸을 꽉 채움
+fn fluid_surface(level: FluidLevel, above: Cube) -> f32 {
+    if above.fluid().is_some() {
+        1.0
+    } else {
+        level.height()
+    }
+}
+
+/// 옆 칸이 가리는 높이. 유체 옆면은 이 높이보다 위만 그림
+fn fluid_side_cover(neighbor: Cube, neighbor_above: Cube) -> f32 {
+    if neighbor.is_solid() {
+        1.0
+    } else if let Some(level) = neighbor.fluid() {
+        fluid_surface(level, neighbor_above)
+    } else {
+        0.0
+    }
+}
+
 pub fn create_vertices_for_solid(
-    solid: Solid,
+    tiles: &Tiles,
     x: f32,
     y: f32,
     z: f32,
@@ -379,7 +467,7 @@ pub fn create_vertices_for_solid(
     let mut index_data = Vec::<u16>::new();
 
     if !px_is_solid {
-        let [a, b, c, d] = solid.tex_coord_px();
+        let [a, b, c, d] = tiles.tex_coord_px();
         vertex_data.push(vertex([x + 1.0, y + 0.0, z + 0.0], a));
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 0.0], b));
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 1.0], c));
@@ -392,7 +480,7 @@ pub fn create_vertices_for_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !nx_is_solid {
-        let [a, b, c, d] = solid.tex_coord_nx();
+        let [a, b, c, d] = tiles.tex_coord_nx();
         vertex_data.push(vertex([x + 0.0, y + 0.0, z + 1.0], a));
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 1.0], b));
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 0.0], c));
@@ -405,7 +493,7 @@ pub fn create_vertices_for_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !py_is_solid {
-        let [a, b, c, d] = solid.tex_coord_py();
+        let [a, b, c, d] = tiles.tex_coord_py();
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 0.0], a));
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 0.0], b));
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 1.0], c));
@@ -418,7 +506,7 @@ pub fn create_vertices_for_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !ny_is_solid {
-        let [a, b, c, d] = solid.tex_coord_ny();
+        let [a, b, c, d] = tiles.tex_coord_ny();
         vertex_data.push(vertex([x + 1.0, y + 0.0, z + 1.0], a));
         vertex_data.push(vertex([x + 0.0, y + 0.0, z + 1.0], b));
         vertex_data.push(vertex([x + 0.0, y + 0.0, z + 0.0], c));
@@ -431,7 +519,7 @@ pub fn create_vertices_for_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !pz_is_solid {
-        let [a, b, c, d] = solid.tex_coord_pz();
+        let [a, b, c, d] = tiles.tex_coord_pz();
         vertex_data.push(vertex([x + 0.0, y + 0.0, z + 1.0], a));
         vertex_data.push(vertex([x + 1.0, y + 0.0, z + 1.0], b));
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 1.0], c));
@@ -444,7 +532,7 @@ pub fn create_vertices_for_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !nz_is_solid {
-        let [a, b, c, d] = solid.tex_coord_nz();
+        let [a, b, c, d] = tiles.tex_coord_nz();
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 0.0], a));
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 0.0], b));
         vertex_data.push(vertex([x + 1.0, y + 0.0, z + 0.0], c));
@@ -460,7 +548,7 @@ pub fn create_vertices_for_solid(
 }
 
 pub fn create_vertices_for_translucent(
-    translucent: Translucent,
+    tiles: &Tiles,
     x: f32,
     y: f32,
     z: f32,
@@ -478,7 +566,7 @@ pub fn create_vertices_for_translucent(
     let mut index_data = Vec::<u16>::new();
 
     if !px_is_translucent_or_solid {
-        let [a, b, c, d] = translucent.tex_coord();
+        let [a, b, c, d] = tiles.tex_coord();
         vertex_data.push(vertex([x + 1.0, y + 0.0, z + 0.0], a));
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 0.0], b));
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 1.0], c));
@@ -491,7 +579,7 @@ pub fn create_vertices_for_translucent(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !nx_is_translucent_or_solid {
-        let [a, b, c, d] = translucent.tex_coord();
+        let [a, b, c, d] = tiles.tex_coord();
         vertex_data.push(vertex([x + 0.0, y + 0.0, z + 1.0], a));
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 1.0], b));
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 0.0], c));
@@ -504,7 +592,7 @@ pub fn create_vertices_for_translucent(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !py_is_translucent_or_solid {
-        let [a, b, c, d] = translucent.tex_coord();
+        let [a, b, c, d] = tiles.tex_coord();
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 0.0], a));
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 0.0], b));
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 1.0], c));
@@ -517,7 +605,7 @@ pub fn create_vertices_for_translucent(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !ny_is_translucent_or_solid {
-        let [a, b, c, d] = translucent.tex_coord();
+        let [a, b, c, d] = tiles.tex_coord();
         vertex_data.push(vertex([x + 1.0, y + 0.0, z + 1.0], a));
         vertex_data.push(vertex([x + 0.0, y + 0.0, z + 1.0], b));
         vertex_data.push(vertex([x + 0.0, y + 0.0, z + 0.0], c));
@@ -530,7 +618,7 @@ pub fn create_vertices_for_translucent(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !pz_is_translucent_or_solid {
-        let [a, b, c, d] = translucent.tex_coord();
+        let [a, b, c, d] = tiles.tex_coord();
         vertex_data.push(vertex([x + 0.0, y + 0.0, z + 1.0], a));
         vertex_data.push(vertex([x + 1.0, y + 0.0, z + 1.0], b));
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 1.0], c));
@@ -543,7 +631,7 @@ pub fn create_vertices_for_translucent(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !nz_is_translucent_or_solid {
-        let [a, b, c, d] = translucent.tex_coord();
+        let [a, b, c, d] = tiles.tex_coord();
         vertex_data.push(vertex([x + 0.0, y + 1.0, z + 0.0], a));
         vertex_data.push(vertex([x + 1.0, y + 1.0, z + 0.0], b));
         vertex_data.push(vertex([x + 1.0, y + 0.0, z + 0.0], c));
@@ -559,7 +647,8 @@ pub fn create_vertices_for_translucent(
 }
 
 pub fn create_vertices_for_filtered_solid(
-    filtered_solid: FilteredSolid,
+    tiles: &Tiles,
+    filter_tiles: &Tiles,
     x: f32,
     y: f32,
     z: f32,
@@ -578,7 +667,7 @@ pub fn create_vertices_for_filtered_solid(
     let mut index_data = Vec::<u16>::new();
 
     if !px_is_solid {
-        let ([a, b, c, d], [e, f, g, h]) = filtered_solid.extras_px();
+        let ([a, b, c, d], [e, f, g, h]) = (tiles.tex_coord_px(), filter_tiles.tex_coord_px());
         vertex_data.push(filtered_vertex(
             [x + 1.0, y + 0.0, z + 0.0],
             a,
@@ -611,7 +700,7 @@ pub fn create_vertices_for_filtered_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !nx_is_solid {
-        let ([a, b, c, d], [e, f, g, h]) = filtered_solid.extras_nx();
+        let ([a, b, c, d], [e, f, g, h]) = (tiles.tex_coord_nx(), filter_tiles.tex_coord_nx());
         vertex_data.push(filtered_vertex(
             [x + 0.0, y + 0.0, z + 1.0],
             a,
@@ -644,7 +733,7 @@ pub fn create_vertices_for_filtered_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !py_is_solid {
-        let ([a, b, c, d], [e, f, g, h]) = filtered_solid.extras_py();
+        let ([a, b, c, d], [e, f, g, h]) = (tiles.tex_coord_py(), filter_tiles.tex_coord_py());
         vertex_data.push(filtered_vertex(
             [x + 1.0, y + 1.0, z + 0.0],
             a,
@@ -677,7 +766,7 @@ pub fn create_vertices_for_filtered_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !ny_is_solid {
-        let ([a, b, c, d], [e, f, g, h]) = filtered_solid.extras_ny();
+        let ([a, b, c, d], [e, f, g, h]) = (tiles.tex_coord_ny(), filter_tiles.tex_coord_ny());
         vertex_data.push(filtered_vertex(
             [x + 1.0, y + 0.0, z + 1.0],
             a,
@@ -710,7 +799,7 @@ pub fn create_vertices_for_filtered_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !pz_is_solid {
-        let ([a, b, c, d], [e, f, g, h]) = filtered_solid.extras_pz();
+        let ([a, b, c, d], [e, f, g, h]) = (tiles.tex_coord_pz(), filter_tiles.tex_coord_pz());
         vertex_data.push(filtered_vertex(
             [x + 0.0, y + 0.0, z + 1.0],
             a,
@@ -743,7 +832,7 @@ pub fn create_vertices_for_filtered_solid(
         index_data.push(offset + vertex_data.len() as u16 - 4);
     }
     if !nz_is_solid {
-        let ([a, b, c, d], [e, f, g, h]) = filtered_solid.extras_nz();
+        let ([a, b, c, d], [e, f, g, h]) = (tiles.tex_coord_nz(), filter_tiles.tex_coord_nz());
         vertex_data.push(filtered_vertex(
             [x + 0.0, y + 1.0, z + 0.0],
             a,
@@ -778,8 +867,112 @@ pub fn create_vertices_for_filtered_solid(
     (vertex_data, index_data)
 }
 
+/// 유체는 표면이 `height`까지만 올라오고, 옆면은 `side_covers` (px, nx, py, ny)
+/// 높이부터 표면까지만 그림
+#[allow(clippy::too_many_arguments)]
+pub fn create_vertices_for_fluid(
+    tiles: &Tiles,
+    filter_tiles: &Tiles,
+    x: f32,
+    y: f32,
+    z: f32,
+    height: f32,
+    side_covers: [f32; 4],
+    pz_is_visible: bool,
+    nz_is_visible: bool,
+    index: usize,
+) -> (Vec<Vertex>, Vec<u16>) {
+    let offset = index as u16;
+
+    let mut vertex_data = Vec::<Vertex>::new();
+    let mut index_data = Vec::<u16>::new();
+    let mut push_quad = |corners: [[f32; 3]; 4], tc: [[f32; 2]; 4], ftc: [[f32; 2]; 4]| {
+        for i in 0..4 {
+            vertex_data.push(filtered_vertex(corners[i], tc[i], ftc[i], WATER_COLOR));
+        }
+        let last = offset + vertex_data.len() as u16;
+        index_data.extend_from_slice(&[last - 4, last - 3, last - 2, last - 2, last - 1, last - 4]);
+    };
+
+    let [px, nx, py, ny] = side_covers.map(|cover| z + cover);
+    let top = z + height;
+    if px < top {
+        push_quad(
+            [
+                [x + 1.0, y + 0.0, px],
+                [x + 1.0, y + 1.0, px],
+                [x + 1.0, y + 1.0, top],
+                [x + 1.0, y + 0.0, top],
+            ],
+            tiles.tex_coord_px(),
+            filter_tiles.tex_coord_px(),
+        );
+    }
+    if nx < top {
+        push_quad(
+            [
+                [x + 0.0, y + 0.0, top],
+                [x + 0.0, y + 1.0, top],
+                [x + 0.0, y + 1.0, nx],
+                [x + 0.0, y + 0.0, nx],
+            ],
+            tiles.tex_coord_nx(),
+            filter_tiles.tex_coord_nx(),
+        );
+    }
+    if py < top {
+        push_quad(
+            [
+                [x + 1.0, y + 1.0, py],
+                [x + 0.0, y + 1.0, py],
+                [x + 0.0, y + 1.0, top],
+                [x + 1.0, y + 1.0, top],
+            ],
+            tiles.tex_coord_py(),
+            filter_tiles.tex_coord_py(),
+        );
+    }
+    if ny < top {
+        push_quad(
+            [
+                [x + 1.0, y + 0.0, top],
+                [x + 0.0, y + 0.0, top],
+                [x + 0.0, y + 0.0, ny],
+                [x + 1.0, y + 0.0, ny],
+            ],
+            tiles.tex_coord_ny(),
+            filter_tiles.tex_coord_ny(),
+        );
+    }
+    if pz_is_visible {
+        push_quad(
+            [
+                [x + 0.0, y + 0.0, top],
+                [x + 1.0, y + 0.0, top],
+                [x + 1.0, y + 1.0, top],
+                [x + 0.0, y + 1.0, top],
+            ],
+            tiles.tex_coord_pz(),
+            filter_tiles.tex_coord_pz(),
+        );
+    }
+    if nz_is_visible {
+        push_quad(
+            [
+                [x + 0.0, y + 1.0, z],
+                [x + 1.0, y + 1.0, z],
+                [x + 1.0, y + 0.0, z],
+                [x + 0.0, y + 0.0, z],
+            ],
+            tiles.tex_coord_nz(),
+            filter_tiles.tex_coord_nz(),
+        );
+    }
+    (vertex_data, index_data)
+}
+
 pub fn create_vertices_for_plantlike(
-    plantlike: Plantlike,
+    tiles: &Tiles,
     x: f32,
     y: f32,
     z: f32,
@@ -790,7 +983,7 @@ pub fn create_vertices_for_plantlike(
     let mut vertex_data = Vec::<Vertex>::new();
     let mut index_data = Vec::<u16>::new();
 
-    let [a, b, c, d] = plantlike.tex_coord();
+    let [a, b, c, d] = tiles.tex_coord();
     vertex_data.push(vertex([x + 1.0, y + 0.0, z + 0.0], a));
     vertex_data.push(vertex([x + 0.0, y + 1.0, z + 0.0], b));
     vertex_data.push(vertex([x + 0.0, y + 1.0, z + 1.0], c));
@@ -815,7 +1008,7 @@ pub fn create_vertices_for_plantlike(
 }
 
 pub fn create_vertices_for_harvestable(
-    harvestable: Harvestable,
+    tiles: &Tiles,
     x: f32,
     y: f32,
     z: f32,
@@ -826,7 +1019,7 @@ pub fn create_vertices_for_harvestable(
     let mut vertex_data = Vec::<Vertex>::new();
     let mut index_data = Vec::<u16>::new();
 
-    let [a, b, c, d] = harvestable.tex_coord();
+    let [a, b, c, d] = tiles.tex_coord();
     vertex_data.push(vertex([x + 0.75, y + 0.0, z + 0.0], a));
     vertex_data.push(vertex([x + 0.75, y + 1.0, z + 0.0], b));
     vertex_data.push(vertex([x + 0.75, y + 1.0, z + 1.0], c));
@@ -870,8 +1063,8 @@ pub fn create_vertices_for_harvestable(
     (vertex_data, index_data)
 }
 
-pub fn create_vertices_for_custom(
-    custom: Custom,
+pub fn create_vertices_for_cactus(
+    tiles: &Tiles,
     x: f32,
     y: f32,
     z: f32,
@@ -886,106 +1079,72 @@ pub fn create_vertices_for_custom(
     let mut vertex_data_translucent = Vec::<Vertex>::new();
     let mut index_data_translucent = Vec::<u16>::new();
 
-    match custom {
-        Custom::Cactus => {
-            vertex_data_translucent.push(vertex([x + 0.9375, y + 0.0, z + 0.0], [7.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 0.9375, y + 1.0, z + 0.0], [6.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 0.9375, y + 1.0, z + 1.0], [6.0, 4.0]));
-            vertex_data_translucent.push(vertex([x + 0.9375, y + 0.0, z + 1.0], [7.0, 4.0]));
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            vertex_data_translucent.push(vertex([x + 0.0625, y + 0.0, z + 1.0], [7.0, 4.0]));
-            vertex_data_translucent.push(vertex([x + 0.0625, y + 1.0, z + 1.0], [6.0, 4.0]));
-            vertex_data_translucent.push(vertex([x + 0.0625, y + 1.0, z + 0.0], [6.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 0.0625, y + 0.0, z + 0.0], [7.0, 5.0]));
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            vertex_data_translucent.push(vertex([x + 1.0, y + 0.9375, z + 0.0], [6.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 0.0, y + 0.9375, z + 0.0], [7.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 0.0, y + 0.9375, z + 1.0], [7.0, 4.0]));
-            vertex_data_translucent.push(vertex([x + 1.0, y + 0.9375, z + 1.0], [6.0, 4.0]));
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            vertex_data_translucent.push(vertex([x + 1.0, y + 0.0625, z + 1.0], [6.0, 4.0]));
-            vertex_data_translucent.push(vertex([x + 0.0, y + 0.0625, z + 1.0], [7.0, 4.0]));
-            vertex_data_translucent.push(vertex([x + 0.0, y + 0.0625, z + 0.0], [7.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 1.0, y + 0.0625, z + 0.0], [6.0, 5.0]));
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            vertex_data_translucent.push(vertex([x + 0.0, y + 0.0, z + 1.0], [6.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 1.0, y + 0.0, z + 1.0], [5.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 1.0, y + 1.0, z + 1.0], [5.0, 4.0]));
-            vertex_data_translucent.push(vertex([x + 0.0, y + 1.0, z + 1.0], [6.0, 4.0]));
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            vertex_data_translucent.push(vertex([x + 0.0, y + 1.0, z + 0.0], [8.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 1.0, y + 1.0, z + 0.0], [7.0, 5.0]));
-            vertex_data_translucent.push(vertex([x + 1.0, y + 0.0, z + 0.0], [7.0, 4.0]));
-            vertex_data_translucent.push(vertex([x + 0.0, y + 0.0, z + 0.0], [8.0, 4.0]));
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
-            index_data_translucent
-                .push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
-        }
-    }
+    let [a, b, c, d] = tiles.tex_coord_px();
+    vertex_data_translucent.push(vertex([x + 0.9375, y + 0.0, z + 0.0], a));
+    vertex_data_translucent.push(vertex([x + 0.9375, y + 1.0, z + 0.0], b));
+    vertex_data_translucent.push(vertex([x + 0.9375, y + 1.0, z + 1.0], c));
+    vertex_data_translucent.push(vertex([x + 0.9375, y + 0.0, z + 1.0], d));
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    let [a, b, c, d] = tiles.tex_coord_nx();
+    vertex_data_translucent.push(vertex([x + 0.0625, y + 0.0, z + 1.0], a));
+    vertex_data_translucent.push(vertex([x + 0.0625, y + 1.0, z + 1.0], b));
+    vertex_data_translucent.push(vertex([x + 0.0625, y + 1.0, z + 0.0], c));
+    vertex_data_translucent.push(vertex([x + 0.0625, y + 0.0, z + 0.0], d));
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    let [a, b, c, d] = tiles.tex_coord_py();
+    vertex_data_translucent.push(vertex([x + 1.0, y + 0.9375, z + 0.0], a));
+    vertex_data_translucent.push(vertex([x + 0.0, y + 0.9375, z + 0.0], b));
+    vertex_data_translucent.push(vertex([x + 0.0, y + 0.9375, z + 1.0], c));
+    vertex_data_translucent.push(vertex([x + 1.0, y + 0.9375, z + 1.0], d));
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    let [a, b, c, d] = tiles.tex_coord_ny();
+    vertex_data_translucent.push(vertex([x + 1.0, y + 0.0625, z + 1.0], a));
+    vertex_data_translucent.push(vertex([x + 0.0, y + 0.0625, z + 1.0], b));
+    vertex_data_translucent.push(vertex([x + 0.0, y + 0.0625, z + 0.0], c));
+    vertex_data_translucent.push(vertex([x + 1.0, y + 0.0625, z + 0.0], d));
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    let [a, b, c, d] = tiles.tex_coord_pz();
+    vertex_data_translucent.push(vertex([x + 0.0, y + 0.0, z + 1.0], a));
+    vertex_data_translucent.push(vertex([x + 1.0, y + 0.0, z + 1.0], b));
+    vertex_data_translucent.push(vertex([x + 1.0, y + 1.0, z + 1.0], c));
+    vertex_data_translucent.push(vertex([x + 0.0, y + 1.0, z + 1.0], d));
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    let [a, b, c, d] = tiles.tex_coord_nz();
+    vertex_data_translucent.push(vertex([x + 0.0, y + 1.0, z + 0.0], a));
+    vertex_data_translucent.push(vertex([x + 1.0, y + 1.0, z + 0.0], b));
+    vertex_data_translucent.push(vertex([x + 1.0, y + 0.0, z + 0.0], c));
+    vertex_data_translucent.push(vertex([x + 0.0, y + 0.0, z + 0.0], d));
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 3);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 2);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 1);
+    index_data_translucent.push(translucent_offset + vertex_data_translucent.len() as u16 - 4);
 
     (
         (vertex_data_opaque, index_data_opaque),
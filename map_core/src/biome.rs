@@ -0,0 +1,182 @@
+use map_types::{blocks, Biome, Cube};
+
+use crate::WATER_LEVEL;
+
+/// Noise values a column's biome is picked from, each mostly within -0.3..0.3.
+#[derive(Clone, Copy)]
+pub struct Climate {
+    pub temperature: f32,
+    pub humidity: f32,
+    /// Rare extremes turn land into mushroom fields.
+    pub weirdness: f32,
+    /// Within -1..1, low towards the open sea. It also shapes the ground, so the sea
+    /// lies where it is below `COAST`.
+    pub continentalness: f32,
+}
+
+/// Continentalness below which the ground meets the sea: flooded columns there are
+/// ocean, and those just above the water beach.
+const COAST: f32 = -0.1;
+
+/// Picks the biome of a column whose ground ends at `height`.
+/// Continentalness decides seas and coasts, height only whether a coastal column is
+/// under water; climate decides the rest. Ground below the water inland is a basin
+/// of whatever land is around it.
+pub fn choose(climate: Climate, height: usize) -> Biome {
+    let coastal = climate.continentalness < COAST;
+    if coastal && height <= WATER_LEVEL {
+        return Biome::Ocean;
+    }
+    if climate.weirdness > 0.2 {
+        return Biome::MushroomFields;
+    }
+    let cold = climate.temperature < -0.1;
+    if coastal && height <= WATER_LEVEL + 2 && !cold {
+        return Biome::Beach;
+    }
+    match (climate.temperature, climate.humidity) {
+        (t, h) if t < -0.1 && h < 0.0 => Biome::SnowyTundra,
+        (t, _) if t < -0.1 => Biome::Taiga,
+        (t, h) if t > 0.08 && h < 0.0 => Biome::Desert,
+        (_, h) if h > 0.05 => Biome::Forest,
+        _ => Biome::Plains,
+    }
+}
+
+/// Grass tint as `[r, g, b, strength]`.
+pub fn tint(biome: Biome) -> [f32; 4] {
+    match biome {
+        Biome::Ocean => [0.45, 0.75, 0.4, 0.8],
+        Biome::Beach => [0.6, 0.8, 0.4, 0.8],
+        Biome::Plains => [0.55, 0.85, 0.35, 0.8],
+        Biome::Desert => [0.75, 0.72, 0.4, 0.85],
+        Biome::Forest => [0.3, 0.68, 0.25, 0.85],
+        Biome::Taiga => [0.4, 0.62, 0.5, 0.8],
+        Biome::SnowyTundra => [0.7, 0.8, 0.75, 0.6],
+        Biome::MushroomFields => [0.45, 0.8, 0.3, 0.8],
+        Biome::River => [0.45, 0.75, 0.4, 0.8],
+        Biome::Nether => [0.5, 0.4, 0.3, 0.8],
+    }
+}
+
+/// The top block of the ground.
+pub fn surface(biome: Biome) -> Cube {
+    match biome {
+        Biome::Ocean | Biome::River => blocks::GRAVEL,
+        Biome::Beach | Biome::Desert => blocks::SAND,
+        Biome::SnowyTundra => blocks::SNOWY_GRASS_BLOCK,
+        Biome::MushroomFields => blocks::MYCELIUM,
+        Biome::Plains | Biome::Forest | Biome::Taiga => blocks::GRASS_BLOCK,
+        Biome::Nether => blocks::NETHERRACK,
+    }
+}
+
+/// The block right below the surface.
+pub fn filler(biome: Biome) -> Cube {
+    match biome {
+        Biome::Ocean | Biome::River => blocks::GRAVEL,
+        Biome::Beach | Biome::Desert => blocks::SAND,
+        Biome::Nether => blocks::NETHERRACK,
+        _ => blocks::DIRT,
+    }
+}
+
+/// What grows on the surface. `density` and `kind` are independent noise values
+/// within -1..1; `density` decides whether anything grows and `kind` what.
+pub fn decoration(biome: Biome, density: f32, kind: f32) -> Cube {
+    match biome {
+        Biome::Desert if density > 0.1949 => {
+            if kind > -0.2 {
+                blocks::DEAD_BUSH
+            } else {
+                blocks::CACTUS
+            }
+        }
+        Biome::Plains if density > 0.2042 => {
+            if kind > 0.2 {
+                blocks::RED_FLOWER
+            } else if kind < -0.2 {
+                blocks::YELLOW_FLOWER
+            } else {
+                blocks::GRASS
+            }
+        }
+        Biome::Forest if density > 0.1 => {
+            if kind > 0.35 {
+                blocks::RED_FLOWER
+            } else {
+                blocks::GRASS
+            }
+        }
+        Biome::Taiga if density > 0.25 => blocks::GRASS,
+        Biome::MushroomFields if density > 0.25 => {
+            if kind > 0.0 {
+                blocks::RED_MUSHROOM
+            } else {
+                blocks::BROWN_MUSHROOM
+            }
+        }
+        _ => Cube::EMPTY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MILD: Climate = Climate {
+        temperature: 0.0,
+        humidity: 0.0,
+        weirdness: 0.0,
+        continentalness: 0.0,
+    };
+
+    #[test]
+    fn continentalness_decides_seas_and_coasts() {
+        let coast = Climate {
+            continentalness: -0.3,
+            ..MILD
+        };
+        assert_eq!(choose(coast, WATER_LEVEL), Biome::Ocean);
+        assert_eq!(choose(coast, WATER_LEVEL + 1), Biome::Beach);
+        assert_eq!(choose(coast, WATER_LEVEL + 20), Biome::Plains);
+        // 내륙의 낮은 땅은 바다나 해변이 되지 않음
+        assert_eq!(choose(MILD, WATER_LEVEL), Biome::Plains);
+        assert_eq!(choose(MILD, WATER_LEVEL + 1), Biome::Plains);
+        let cold = Climate {
+            temperature: -0.2,
+            humidity: -0.1,
+            ..coast
+        };
+        // 추운 곳은 해변 없이 바로 눈밭
+        assert_eq!(choose(cold, WATER_LEVEL + 1), Biome::SnowyTundra);
+    }
+
+    #[test]
+    fn climate_decides_inland_biomes() {
+        let inland = WATER_LEVEL + 20;
+        let at = |temperature, humidity| {
+            choose(
+                Climate {
+                    temperature,
+                    humidity,
+                    ..MILD
+                },
+                inland,
+            )
+        };
+        assert_eq!(at(0.2, -0.1), Biome::Desert);
+        assert_eq!(at(0.0, 0.1), Biome::Forest);
+        assert_eq!(at(-0.2, 0.1), Biome::Taiga);
+        assert_eq!(
+            choose(
+                Climate {
+                    weirdness: 0.25,
+                    ..MILD
+                },
+                inland
+            ),
+            Biome::MushroomFields
+        );
+    }
+}
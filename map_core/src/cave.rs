@@ -0,0 +1,211 @@
+use map_types::{CHUNK_SIZE, MAP_HEIGHT};
+use noise::{Noise, NoiseLayer};
+
+use crate::chunk_axis;
+
+/// Caverns stay at least this far below the surface so they never swallow the landscape.
+const CHEESE_MIN_DEPTH: usize = 12;
+const CHEESE_THRESHOLD: f32 = 0.28;
+/// Half width of a tunnel in noise units; both noises have to be within it.
+const SPAGHETTI_WIDTH: f32 = 0.045;
+/// Tunnels only reach through this many top blocks where the entrance noise allows it.
+const CRUST_DEPTH: usize = 6;
+const ENTRANCE_THRESHOLD: f32 = 0.12;
+
+/// Cave noise is sampled every `CELL` blocks and interpolated in between.
+pub const CELL: usize = 4;
+const POINTS_XY: usize = CHUNK_SIZE / CELL + 1;
+const POINTS_Z: usize = MAP_HEIGHT / CELL + 1;
+
+/// Decides which ground cubes are hollowed out. Large "cheese" caverns form where one
+/// noise is high, and "spaghetti" tunnels where two independent noises are both near zero.
+#[derive(Clone)]
+pub struct Caves {
+    cheese: Noise,
+    spaghetti: [Noise; 2],
+    entrances: Noise,
+}
+
+impl Caves {
+    pub fn new(seed: u64) -> Caves {
+        let cheese = Noise::new(
+            &[NoiseLayer::new(0.012, 0.8), NoiseLayer::new(0.05, 0.2)],
+            seed.wrapping_add(1),
+        );
+        let spaghetti = [
+            Noise::new(&[NoiseLayer::new(0.02, 1.0)], seed.wrapping_add(2)),
+            Noise::new(&[NoiseLayer::new(0.02, 1.0)], seed.wrapping_add(3)),
+        ];
+        let entrances = Noise::new(&[NoiseLayer::new(0.01, 1.0)], seed.wrapping_add(4));
+        Caves {
+            cheese,
+            spaghetti,
+            entrances,
+        }
+    }
+
+    /// Whether no cave can open the top of the column at world position (x, y).
+    pub fn is_sealed(&self, x: f32, y: f32, height: usize, water: usize) -> bool {
+        is_sealed(self.entrances.noise2(x, y), height, water)
+    }
+
+    /// Samples the cave noise of the chunk whose first cube is at (x_offset, y_offset).
+    pub fn chunk(&self, x_offset: i32, y_offset: i32) -> ChunkCaves {
+        let sample = |noise: &Noise, z_scale: f32| {
+            sample_lattice(noise, x_offset, y_offset, POINTS_Z, z_scale)
+        };
+        let entrances = self
+            .entrances
+            .grid2(&chunk_axis(x_offset), &chunk_axis(y_offset));
+        // z축을 늘려서 샘플링해 동굴이 가로로 넓게 퍼지도록 함
+        ChunkCaves {
+            cheese: sample(&self.cheese, 2.0),
+            spaghetti: [
+                sample(&self.spaghetti[0], 1.5),
+                sample(&self.spaghetti[1], 1.5),
+            ],
+            entrances,
+        }
+    }
+}
+
+/// Cave noise of one chunk, see `Caves::chunk`.
+pub struct ChunkCaves {
+    cheese: Vec<f32>,
+    spaghetti: [Vec<f32>; 2],
+    entrances: Vec<f32>,
+}
+
+impl ChunkCaves {
+    /// Whether the ground cube at (x, y, z) inside the chunk is carved out, in a column
+    /// whose ground ends below `height` and whose water reaches up to `water`.
+    /// Bedrock at z = 0 is never carved.
+    pub fn is_carved(&self, x: usize, y: usize, z: usize, height: usize, water: usize) -> bool {
+        if z == 0 || z >= height {
+            return false;
+        }
+        let depth = height - z;
+
+        if depth >= CHEESE_MIN_DEPTH && interpolate(&self.cheese, x, y, z) > CHEESE_THRESHOLD {
+            return true;
+        }
+
+        if depth <= CRUST_DEPTH && is_sealed(self.entrances[y * CHUNK_SIZE + x], height, water) {
+            return false;
+        }
+        self.spaghetti
+            .iter()
+            .all(|points| interpolate(points, x, y, z).abs() < SPAGHETTI_WIDTH)
+    }
+}
+
+/// Whether tunnels stay out of the top `CRUST_DEPTH` blocks of a column.
+fn is_sealed(entrance: f32, height: usize, water: usize) -> bool {
+    // 물 밑이나 물가는 물이 새지 않도록 막아둠
+    height <= water + 2 || entrance < ENTRANCE_THRESHOLD
+}
+
+/// Samples `noise` every `CELL` blocks over the chunk whose first cube is at
+/// (x_offset, y_offset), from z = 0 up through `points_z` lattice layers.
+pub fn sample_lattice(
+    noise: &Noise,
+    x_offset: i32,
+    y_offset: i32,
+    points_z: usize,
+    z_scale: f32,
+) -> Vec<f32> {
+    let axis = |offset: i32| -> Vec<f32> {
+        (0..POINTS_XY)
+            .map(|i| (offset + (i * CELL) as i32) as f32)
+            .collect()
+    };
+    let zs: Vec<f32> = (0..points_z).map(|k| (k * CELL) as f32 * z_scale).collect();
+    noise.grid3(&axis(x_offset), &axis(y_offset), &zs)
+}
+
+/// Trilinear interpolation between the lattice points around (x, y, z).
+pub fn interpolate(points: &[f32], x: usize, y: usize, z: usize) -> f32 {
+    let at = |i: usize, j: usize, k: usize| points[(k * POINTS_XY + j) * POINTS_XY + i];
+    let (i, j, k) = (x / CELL, y / CELL, z / CELL);
+    let [u, v, w] = [x, y, z].map(|c| (c % CELL) as f32 / CELL as f32);
+    let lerp = |t: f32, a: f32, b: f32| a + t * (b - a);
+    let plane = |k| {
+        lerp(
+            v,
+            lerp(u, at(i, j, k), at(i + 1, j, k)),
+            lerp(u, at(i, j + 1, k), at(i + 1, j + 1, k)),
+        )
+    };
+    lerp(w, plane(k), plane(k + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use map_types::blocks;
+
+    use super::*;
+    use crate::{Map, WATER_LEVEL};
+
+    const HEIGHT: usize = 150;
+
+    /// Calls `f` with the cave noise of every chunk in a `size` x `size` area.
+    fn each_chunk(caves: &Caves, size: i32, mut f: impl FnMut(&ChunkCaves)) {
+        for cx in 0..size {
+            for cy in 0..size {
+                f(&caves.chunk(cx * CHUNK_SIZE as i32, cy * CHUNK_SIZE as i32));
+            }
+        }
+    }
+
+    /// Counts the columns carved at height `z`.
+    fn count_carved(chunk: &ChunkCaves, z: usize) -> usize {
+        (0..CHUNK_SIZE * CHUNK_SIZE)
+            .filter(|i| chunk.is_carved(i % CHUNK_SIZE, i / CHUNK_SIZE, z, HEIGHT, WATER_LEVEL))
+            .count()
+    }
+
+    #[test]
+    fn bedrock_is_never_carved() {
+        let map = Map::new(42);
+        for cx in -2..2 {
+            for cy in -2..2 {
+                let chunk = map.get_chunk(cx, cy);
+                for i in 0..CHUNK_SIZE * CHUNK_SIZE {
+                    let (x, y) = (i % CHUNK_SIZE, i / CHUNK_SIZE);
+                    assert_eq!(chunk.get(x, y, 0), blocks::BEDROCK, "chunk {cx},{cy}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn caves_are_deterministic_per_seed() {
+        let carved = |seed| {
+            let chunk = Caves::new(seed).chunk(-32, 48);
+            (1..HEIGHT)
+                .map(|z| count_carved(&chunk, z))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(carved(42), carved(42));
+        assert_ne!(carved(42), carved(43));
+    }
+
+    #[test]
+    fn surface_opens_only_occasionally() {
+        let mut underground = 0;
+        let mut openings = 0;
+        each_chunk(&Caves::new(42), 16, |chunk| {
+            openings += count_carved(chunk, HEIGHT - 1);
+            underground += count_carved(chunk, 60);
+        });
+        let columns = 256 * 256;
+        assert!(openings > 0, "no cave entrances at all");
+        assert!(
+            openings * 100 < columns,
+            "{} of {} columns open",
+            openings,
+            columns
+        );
+        assert!(underground > openings);
+    }
+}
@@ -1,14 +1,65 @@
-use map_types::{
-    Chunk, Cube, Custom, FilteredSolid, Plantlike, Solid, Translucent, CHUNK_SIZE, MAP_HEIGHT,
-};
+use map_types::{blocks, Biome, Chunk, Cube, Dimension, CHUNK_SIZE, MAP_HEIGHT};
 use noise::{Noise, NoiseLayer};
 
+mod biome;
+mod cave;
+pub mod nether;
+mod ore;
+mod random;
+mod structure;
+mod tree;
+mod village;
+mod water;
+
+use biome::Climate;
+use cave::Caves;
+pub use nether::Nether;
+use water::{Lake, LakeShape, Rivers};
+
 pub const WATER_LEVEL: usize = 111;
 
+/// How many columns around each column its grass tint is averaged over.
+const BLEND_RADIUS: usize = 4;
+
+/// The terrain generator of one dimension.
+#[derive(Clone)]
+pub enum Generator {
+    Overworld(Box<Map>),
+    Nether(Box<Nether>),
+}
+
+impl Generator {
+    pub fn new(dimension: Dimension, seed: u64) -> Generator {
+        match dimension {
+            Dimension::Overworld => Generator::Overworld(Box::new(Map::new(seed))),
+            Dimension::Nether => Generator::Nether(Box::new(Nether::new(seed))),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        match self {
+            Generator::Overworld(map) => map.seed(),
+            Generator::Nether(nether) => nether.seed(),
+        }
+    }
+
+    pub fn get_chunk(&self, x: i32, y: i32) -> Chunk {
+        match self {
+            Generator::Overworld(map) => map.get_chunk(x, y),
+            Generator::Nether(nether) => nether.get_chunk(x, y),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct Map {
+    seed: u64,
     main_noise: Noise,
-    height_base_noise: Noise,
+    /// Continent-scale noise: it lifts the ground out of the sea where it is high, and
+    /// tells oceans and coasts from inland.
+    continentalness: Noise,
+    caves: Caves,
+    rivers: Rivers,
 }
 
 impl Map {
@@ -24,7 +75,7 @@ impl Map {
             ],
             seed,
         );
-        let height_base_noise = Noise::new(
+        let continentalness = Noise::new(
             &[
                 NoiseLayer::new(0.00142, 0.9),
                 NoiseLayer::new(0.0042, 0.07),
@@ -33,121 +84,300 @@ impl Map {
             seed,
         );
         Map {
+            seed,
             main_noise,
-            height_base_noise,
+            continentalness,
+            caves: Caves::new(seed),
+            rivers: Rivers::new(seed),
+        }
+    }
+
+    /// The seed every generated chunk depends on.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    fn climate(&self, actual_x: f32, actual_y: f32) -> Climate {
+        let n = |factor: f32, z: f32| {
+            self.main_noise
+                .noise3(actual_x * factor, actual_y * factor, z)
+        };
+        Climate {
+            temperature: n(0.0021, 2424.0),
+            humidity: n(0.0042, 4242.0),
+            weirdness: n(0.0017, 8484.0),
+            continentalness: self.continentalness.noise2(actual_x, actual_y),
+        }
+    }
+
+    /// Ground height at world position (x, y) with river valleys cut in, and
+    /// whether the position lies in a river channel.
+    fn terrain(&self, actual_x: f32, actual_y: f32) -> (usize, bool) {
+        let n = |factor: f32, z: f32| {
+            self.main_noise
+                .noise3(actual_x * factor, actual_y * factor, z)
+        };
+        let noise = TerrainNoise {
+            continentalness: self.continentalness.noise2(actual_x, actual_y),
+            detail: n(0.0618, 0.0),
+            scale: n(0.000922, 42.0),
+        };
+        self.shape_terrain(actual_x, actual_y, noise)
+    }
+
+    fn shape_terrain(&self, actual_x: f32, actual_y: f32, noise: TerrainNoise) -> (usize, bool) {
+        let height = (lerp(noise.continentalness / 4.0 + 0.5, 22.2, 222.2)
+            + (noise.detail * noise.scale) * 342.0)
+            .clamp(22.2, 222.2) as usize;
+        self.rivers.carve(actual_x, actual_y, height)
+    }
+
+    /// The column at world position (x, y). `lakes` must hold every lake near it.
+    fn column(&self, actual_x: f32, actual_y: f32, lakes: &[Lake]) -> Column {
+        let terrain = self.terrain(actual_x, actual_y);
+        self.shape_column(
+            actual_x,
+            actual_y,
+            terrain,
+            || self.climate(actual_x, actual_y),
+            lakes,
+        )
+    }
+
+    /// The columns at every combination of `xs` and `ys`, with x changing fastest.
+    /// Samples the noise of all of them at once, which is much faster than calling
+    /// `column` for each.
+    fn columns(&self, xs: &[f32], ys: &[f32], lakes: &[Lake]) -> Vec<Column> {
+        let (mut scaled_xs, mut scaled_ys) = (Vec::new(), Vec::new());
+        let mut grid = |factor: f32, z: f32| {
+            scale_into(&mut scaled_xs, xs, factor);
+            scale_into(&mut scaled_ys, ys, factor);
+            self.main_noise.grid3(&scaled_xs, &scaled_ys, &[z])
+        };
+        let continentalness = self.continentalness.grid2(xs, ys);
+        let detail = grid(0.0618, 0.0);
+        let scale = grid(0.000922, 42.0);
+        // 강이 아닌 칸만 기후가 필요하지만 한꺼번에 구하는 편이 더 빠름
+        let temperature = grid(0.0021, 2424.0);
+        let humidity = grid(0.0042, 4242.0);
+        let weirdness = grid(0.0017, 8484.0);
+
+        let mut columns = Vec::with_capacity(xs.len() * ys.len());
+        for &y in ys {
+            for &x in xs {
+                let i = columns.len();
+                let noise = TerrainNoise {
+                    continentalness: continentalness[i],
+                    detail: detail[i],
+                    scale: scale[i],
+                };
+                let climate = || Climate {
+                    temperature: temperature[i],
+                    humidity: humidity[i],
+                    weirdness: weirdness[i],
+                    continentalness: continentalness[i],
+                };
+                let terrain = self.shape_terrain(x, y, noise);
+                columns.push(self.shape_column(x, y, terrain, climate, lakes));
+            }
+        }
+        columns
+    }
+
+    /// Lays lakes over the ground from `shape_terrain` and picks the biome.
+    fn shape_column(
+        &self,
+        actual_x: f32,
+        actual_y: f32,
+        (mut height, river): (usize, bool),
+        climate: impl FnOnce() -> Climate,
+        lakes: &[Lake],
+    ) -> Column {
+        let mut water = WATER_LEVEL;
+        let mut bank = false;
+        for lake in lakes {
+            match lake.shape(actual_x, actual_y) {
+                LakeShape::Water { bed, level } => {
+                    height = height.min(bed);
+                    water = level;
+                }
+                LakeShape::Bank => bank = true,
+                LakeShape::Outside => {}
+            }
+        }
+        let biome = if river && height <= WATER_LEVEL {
+            Biome::River
+        } else {
+            biome::choose(climate(), height)
+        };
+        Column {
+            height,
+            water,
+            biome,
+            bank: bank && height > water,
         }
     }
 
-    // TODO: optimize
     pub fn get_chunk(&self, x: i32, y: i32) -> Chunk {
-        let mut cubes = [Cube::Empty; MAP_HEIGHT * CHUNK_SIZE * CHUNK_SIZE];
-        let mut biome_colors = [[0.0f32; 4]; CHUNK_SIZE * CHUNK_SIZE];
+        let mut chunk = Chunk::new();
         let x_offset = x * CHUNK_SIZE as i32;
         let y_offset = y * CHUNK_SIZE as i32;
 
+        // 경계에서 풀 색이 부드럽게 바뀌도록 주변 BLEND_RADIUS 칸까지 바이옴을 구해 평균냄
+        const SPAN: usize = CHUNK_SIZE + 2 * BLEND_RADIUS;
+        let lakes = Lake::near_chunk(self, x, y, BLEND_RADIUS as i32);
+        let span = |offset: i32| -> Vec<f32> {
+            (0..SPAN)
+                .map(|i| (offset + i as i32 - BLEND_RADIUS as i32) as f32)
+                .collect()
+        };
+        let columns = self.columns(&span(x_offset), &span(y_offset), &lakes);
+
+        let caves = self.caves.chunk(x_offset, y_offset);
+        // 선인장과 풀 같은 장식을 고르는 노이즈
+        let decorations = self.main_noise.grid3(
+            &chunk_axis(x_offset),
+            &chunk_axis(y_offset),
+            &[420.0, 402.0],
+        );
+
         for x in 0..CHUNK_SIZE {
             for y in 0..CHUNK_SIZE {
-                let actual_x = x_offset as f32 + x as f32;
-                let actual_y = y_offset as f32 + y as f32;
-                macro_rules! n {
-                    ($factor:expr, $z:expr) => {
-                        self.main_noise
-                            .noise3(actual_x * $factor, actual_y * $factor, $z)
-                    };
+                let decoration = [0, 1].map(|k| decorations[(k * CHUNK_SIZE + y) * CHUNK_SIZE + x]);
+
+                let Column {
+                    height,
+                    water,
+                    biome,
+                    bank,
+                } = columns[(y + BLEND_RADIUS) * SPAN + x + BLEND_RADIUS];
+                let mut color = [0.0; 4];
+                for j in y..=y + 2 * BLEND_RADIUS {
+                    for i in x..=x + 2 * BLEND_RADIUS {
+                        let tint = biome::tint(columns[j * SPAN + i].biome);
+                        for (sum, value) in color.iter_mut().zip(tint) {
+                            *sum += value;
+                        }
+                    }
                 }
+                let samples = ((2 * BLEND_RADIUS + 1) * (2 * BLEND_RADIUS + 1)) as f32;
+                chunk.biome_colors[y * CHUNK_SIZE + x] = color.map(|sum| sum / samples);
+                chunk.biomes[y * CHUNK_SIZE + x] = biome;
+
+                // 입구가 뚫린 곳에는 식물이 떠 있지 않도록 함
+                let opened = caves.is_carved(x, y, height - 1, height, water);
 
-                let humidity = n!(0.0042, 4242.0);
-                let biome1 = n!(0.042, 4242.0);
-                let biome2 = n!(0.042, 42042.0);
-                let biome3 = n!(0.042, 424242.0);
-                let biome4 = n!(0.042, 420420.0);
-                let is_sand = humidity < -0.2;
-                let height = (lerp(
-                    self.height_base_noise.noise2(actual_x, actual_y) / 4.0 + 0.5,
-                    22.2,
-                    222.2,
-                ) + (n!(0.0618, 0.0) * n!(0.000922, 42.0)) * 342.0)
-                    .clamp(22.2, 222.2) as usize;
-                biome_colors[y * CHUNK_SIZE + x] = [
-                    (de_lerp(biome1, -0.1, 0.1).sin() / 2.0 + 0.5).powi(2),
-                    (de_lerp(biome2, -0.1, 0.1).sin() / 2.0 + 0.5).powi(2),
-                    (de_lerp(biome3, -0.1, 0.1).sin() / 2.0 + 0.5).powi(2),
-                    (de_lerp(biome4, -0.1, 0.1).sin() / 2.0 + 0.5).powi(8),
-                ];
                 for z in 0..MAP_HEIGHT {
-                    cubes[z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x] = if z == 0 {
-                        Cube::Solid(Solid::Bedrock)
+                    let cube = if z == 0 {
+                        blocks::BEDROCK
+                    } else if caves.is_carved(x, y, z, height, water)
+                        || (opened && (z == height || z == height + 1))
+                    {
+                        Cube::EMPTY
                     } else if height + 1 == z
-                        && is_sand
-                        && z > WATER_LEVEL
-                        && n!(1.0, 420.0) > 0.1949
-                        && n!(1.0, 402.0) <= -0.2
+                        && height > water
+                        && biome == Biome::Desert
+                        && decoration[0] > 0.1949
+                        && decoration[1] <= -0.2
                     {
-                        Cube::Custom(Custom::Cactus)
+                        blocks::CACTUS
                     } else if height < z {
-                        if z <= WATER_LEVEL {
-                            Cube::Translucent(Translucent::Ice) // TODO: water
+                        if z <= water {
+                            blocks::WATER
                         } else {
-                            Cube::Empty
+                            Cube::EMPTY
                         }
                     } else if height == z {
-                        if z <= WATER_LEVEL {
-                            Cube::Translucent(Translucent::Ice) // TODO: water
-                        } else if is_sand && n!(1.0, 420.0) > 0.1949 {
-                            let n = n!(1.0, 402.0);
-                            if n > -0.2 {
-                                Cube::Plantlike(Plantlike::DeadBush)
-                            } else {
-                                Cube::Custom(Custom::Cactus)
-                            }
-                        } else if !is_sand && n!(1.0, 420.0) > 0.2042 {
-                            let n = n!(1.0, 402.0);
-                            if n > 0.2 {
-                                Cube::Plantlike(Plantlike::FlowerRed)
-                            } else if n < -0.2 {
-                                Cube::Plantlike(Plantlike::FlowerYellow)
-                            } else {
-                                Cube::Plantlike(Plantlike::Grass)
-                            }
+                        if z <= water {
+                            blocks::WATER
+                        } else if bank {
+                            Cube::EMPTY
                         } else {
-                            Cube::Empty
+                            biome::decoration(biome, decoration[0], decoration[1])
                         }
                     } else if height == z + 1 {
-                        if !is_sand && height == WATER_LEVEL {
-                            Cube::Solid(Solid::Dirt)
-                        } else if !is_sand && height < WATER_LEVEL {
-                            Cube::Solid(Solid::Gravel)
-                        } else if is_sand {
-                            Cube::Solid(Solid::Sand)
+                        if height <= water && biome != Biome::Ocean {
+                            // 강바닥과 호수 바닥
+                            blocks::GRAVEL
+                        } else if bank {
+                            blocks::SAND
                         } else {
-                            Cube::FilteredSolid(FilteredSolid::GrassBlock)
+                            biome::surface(biome)
                         }
                     } else if height == z + 2 {
-                        if !is_sand && height >= WATER_LEVEL {
-                            Cube::Solid(Solid::Dirt)
-                        } else if !is_sand {
-                            Cube::Solid(Solid::Gravel)
+                        if bank {
+                            blocks::SAND
                         } else {
-                            Cube::Solid(Solid::Sand)
+                            biome::filler(biome)
                         }
                     } else {
-                        Cube::Solid(Solid::Stone)
+                        blocks::STONE
                     };
+                    chunk.set(x, y, z, cube);
                 }
             }
         }
 
-        Chunk {
-            cubes,
-            biome_colors,
-        }
+        ore::place(&mut chunk, self.seed, x, y);
+        tree::place(self, &mut chunk, x, y);
+        village::place(self, &mut chunk, x, y);
+
+        chunk
     }
 }
 
+/// The noise values the ground height of a column is made of.
+#[derive(Clone, Copy)]
+struct TerrainNoise {
+    continentalness: f32,
+    detail: f32,
+    scale: f32,
+}
+
+/// What the generator knows about one column before placing cubes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Column {
+    /// The lowest cube above the ground.
+    height: usize,
+    /// Water fills every cube from `height` up to this one.
+    water: usize,
+    biome: Biome,
+    /// Sandy shore around a lake.
+    bank: bool,
+}
+
+/// World coordinates of the cubes of a chunk along one axis, for sampling noise grids.
+fn chunk_axis(offset: i32) -> Vec<f32> {
+    (0..CHUNK_SIZE)
+        .map(|i| (offset + i as i32) as f32)
+        .collect()
+}
+
+/// Refills `scaled` with `coords` multiplied by `factor`, keeping its allocation.
+fn scale_into(scaled: &mut Vec<f32>, coords: &[f32], factor: f32) {
+    scaled.clear();
+    scaled.extend(coords.iter().map(|c| c * factor));
+}
+
 fn lerp(t: f32, a: f32, b: f32) -> f32 {
     a + t * (b - a)
 }
 
-fn de_lerp(result: f32, a: f32, b: f32) -> f32 {
-    (result - a) / (b - a)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batched_columns_match_single_columns() {
+        let map = Map::new(42);
+        let lakes = Lake::near_chunk(&map, 3, -2, BLEND_RADIUS as i32);
+        let xs = chunk_axis(3 * CHUNK_SIZE as i32);
+        let ys = chunk_axis(-2 * CHUNK_SIZE as i32);
+        let columns = map.columns(&xs, &ys, &lakes);
+        for (j, &y) in ys.iter().enumerate() {
+            for (i, &x) in xs.iter().enumerate() {
+                assert_eq!(columns[j * xs.len() + i], map.column(x, y, &lakes));
+            }
+        }
+    }
 }
@@ -0,0 +1,209 @@
+//! The Nether: netherrack caverns closed in by bedrock below and above, with a lava
+//! sea filling their lowest parts.
+
+use map_types::{blocks, Biome, Chunk, Cube, CHUNK_SIZE};
+use noise::{Fractal, Noise, NoiseLayer};
+
+use crate::biome;
+use crate::cave::{self, CELL};
+use crate::chunk_axis;
+use crate::random::ChunkRng;
+
+/// The highest bedrock layer; nothing is generated above it.
+pub const CEILING: usize = 127;
+/// Caverns are flooded with lava up to this height.
+pub const LAVA_LEVEL: usize = 31;
+/// Bedrock reaches up to this many layers into the netherrack at the floor and ceiling.
+const BEDROCK_DEPTH: usize = 4;
+const POINTS_Z: usize = (CEILING + 1) / CELL + 1;
+
+const SALT: u64 = 0x6e65_7468;
+const GLOWSTONE_CLUSTERS: usize = 3;
+const GLOWSTONE_SIZE: usize = 24;
+/// One in this many soul sand tops above the lava grows nether wart.
+const WART_RARITY: usize = 10;
+
+#[derive(Clone)]
+pub struct Nether {
+    seed: u64,
+    density: Noise,
+    soul_sand: Noise,
+}
+
+impl Nether {
+    pub fn new(seed: u64) -> Nether {
+        Nether {
+            seed,
+            // 예전에 직접 쓰던 두 층 (0.02, 0.75), (0.07, 0.25)와 똑같아야 이미
+            // 저장된 청크와 새로 만든 청크가 맞물림
+            density: Fractal::new(0.02, 0.75)
+                .octaves(2)
+                .lacunarity(3.5)
+                .gain(1.0 / 3.0)
+                .seed_step(0)
+                .build(seed.wrapping_add(6)),
+            soul_sand: Noise::new(&[NoiseLayer::new(0.04, 1.0)], seed.wrapping_add(7)),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn get_chunk(&self, x: i32, y: i32) -> Chunk {
+        let mut chunk = Chunk::new();
+        let x_offset = x * CHUNK_SIZE as i32;
+        let y_offset = y * CHUNK_SIZE as i32;
+        let mut rng = ChunkRng::new(self.seed, x, y, SALT);
+
+        // z축을 늘려서 샘플링해 동굴이 가로로 넓게 퍼지도록 함
+        let density = cave::sample_lattice(&self.density, x_offset, y_offset, POINTS_Z, 1.5);
+        let soul_sand = self
+            .soul_sand
+            .grid2(&chunk_axis(x_offset), &chunk_axis(y_offset));
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                chunk.biomes[y * CHUNK_SIZE + x] = Biome::Nether;
+                chunk.biome_colors[y * CHUNK_SIZE + x] = biome::tint(Biome::Nether);
+                let soul_sand = soul_sand[y * CHUNK_SIZE + x] > 0.1;
+
+                for z in 0..=CEILING {
+                    let edge = z.min(CEILING - z);
+                    let cube = if edge == 0
+                        || (edge < BEDROCK_DEPTH && rng.range(0..BEDROCK_DEPTH) >= edge)
+                    {
+                        blocks::BEDROCK
+                    } else if is_solid(cave::interpolate(&density, x, y, z), z) {
+                        blocks::NETHERRACK
+                    } else if z <= LAVA_LEVEL {
+                        blocks::LAVA
+                    } else {
+                        Cube::EMPTY
+                    };
+                    chunk.set(x, y, z, cube);
+                }
+
+                if soul_sand {
+                    self.cover_shore(&mut chunk, &mut rng, x, y);
+                }
+            }
+        }
+
+        for _ in 0..rng.range(0..GLOWSTONE_CLUSTERS + 1) {
+            hang_glowstone(&mut chunk, &mut rng);
+        }
+        chunk
+    }
+
+    /// Turns the netherrack tops near the lava surface into soul sand, with the odd
+    /// nether wart growing on it.
+    fn cover_shore(&self, chunk: &mut Chunk, rng: &mut ChunkRng, x: usize, y: usize) {
+        for z in LAVA_LEVEL - 2..LAVA_LEVEL + 6 {
+            if chunk.get(x, y, z) != blocks::NETHERRACK || chunk.get(x, y, z + 1).is_solid() {
+                continue;
+            }
+            for depth in 0..3 {
+                if chunk.get(x, y, z - depth) == blocks::NETHERRACK {
+                    chunk.set(x, y, z - depth, blocks::SOUL_SAND);
+                }
+            }
+            if chunk.get(x, y, z + 1) == Cube::EMPTY && rng.range(0..WART_RARITY) == 0 {
+                let wart = [
+                    blocks::NETHER_WART_1,
+                    blocks::NETHER_WART_2,
+                    blocks::NETHER_WART_3,
+                ][rng.range(0..3)];
+                chunk.set(x, y, z + 1, wart);
+            }
+        }
+    }
+}
+
+/// Whether the cube at height `z` is netherrack. The cavern space opens up towards
+/// the middle of the Nether and closes near the floor and ceiling.
+fn is_solid(density: f32, z: usize) -> bool {
+    let middle = (z as f32 / CEILING as f32 * 2.0 - 1.0).abs();
+    density + 0.5 * middle.powi(4) - 0.04 > 0.0
+}
+
+/// Grows a glowstone cluster down from a random spot of the cavern ceiling. Clusters
+/// stay inside the chunk, so they only depend on the chunk itself.
+fn hang_glowstone(chunk: &mut Chunk, rng: &mut ChunkRng) {
+    let (x, y) = (rng.range(0..CHUNK_SIZE), rng.range(0..CHUNK_SIZE));
+    let Some(top) = (LAVA_LEVEL + 1..CEILING)
+        .rev()
+        .find(|&z| chunk.get(x, y, z) == Cube::EMPTY && chunk.get(x, y, z + 1).is_solid())
+    else {
+        return;
+    };
+    let mut position = [x as i32, y as i32, top as i32];
+    for _ in 0..GLOWSTONE_SIZE {
+        let [x, y, z] = position.map(|c| c as usize);
+        if chunk.get(x, y, z) == Cube::EMPTY {
+            chunk.set(x, y, z, blocks::GLOWSTONE);
+        }
+        let axis = rng.range(0..3);
+        let step = if axis == 2 {
+            -(rng.range(0..2) as i32)
+        } else {
+            rng.range(0..3) as i32 - 1
+        };
+        position[axis] += step;
+        let limit = if axis == 2 {
+            top as i32 + 1
+        } else {
+            CHUNK_SIZE as i32
+        };
+        position[axis] = position[axis].clamp(0, limit - 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(chunk: &Chunk, cube: Cube) -> usize {
+        (0..CHUNK_SIZE * CHUNK_SIZE)
+            .map(|i| {
+                (0..=CEILING)
+                    .filter(|&z| chunk.get(i % CHUNK_SIZE, i / CHUNK_SIZE, z) == cube)
+                    .count()
+            })
+            .sum()
+    }
+
+    #[test]
+    fn caverns_are_closed_by_bedrock_and_floored_with_lava() {
+        let nether = Nether::new(42);
+        let (mut lava, mut air) = (0, 0);
+        for (cx, cy) in [(0, 0), (-3, 5), (7, -2), (12, 12)] {
+            let chunk = nether.get_chunk(cx, cy);
+            for x in 0..CHUNK_SIZE {
+                for y in 0..CHUNK_SIZE {
+                    assert_eq!(chunk.get(x, y, 0), blocks::BEDROCK);
+                    assert_eq!(chunk.get(x, y, CEILING), blocks::BEDROCK);
+                    assert_eq!(chunk.get(x, y, CEILING + 1), Cube::EMPTY);
+                    assert_ne!(chunk.get(x, y, LAVA_LEVEL), Cube::EMPTY);
+                }
+            }
+            lava += count(&chunk, blocks::LAVA);
+            air += count(&chunk, Cube::EMPTY);
+            assert_eq!(nether.get_chunk(cx, cy), chunk);
+        }
+        assert!(lava > 0, "no lava sea");
+        assert!(air > 0, "no caverns");
+    }
+
+    #[test]
+    fn density_matches_the_layers_chunks_were_saved_with() {
+        let nether = Nether::new(42);
+        let samples = [
+            ((13.0, -7.0, 40.0), 0.4671868),
+            ((-250.0, 96.0, 150.0), -0.05530677),
+            ((1000.5, 3.25, 12.0), 0.25814998),
+        ];
+        for ((x, y, z), value) in samples {
+            assert_eq!(nether.density.noise3(x, y, z), value, "at {x}, {y}, {z}");
+        }
+    }
+}
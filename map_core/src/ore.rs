@@ -0,0 +1,159 @@
+use std::ops::Range;
+
+use map_types::{blocks, Chunk, Cube, CHUNK_SIZE};
+
+use crate::random::ChunkRng;
+
+const SALT: u64 = 0x006f_7265;
+
+struct Ore {
+    cube: Cube,
+    /// Heights a vein may start at.
+    heights: Range<usize>,
+    /// Steps of the random walk that lays out one vein.
+    vein_size: usize,
+    veins_per_chunk: usize,
+}
+
+const ORES: &[Ore] = &[
+    Ore {
+        cube: blocks::COAL_ORE,
+        heights: 5..200,
+        vein_size: 14,
+        veins_per_chunk: 20,
+    },
+    Ore {
+        cube: blocks::IRON_ORE,
+        heights: 5..100,
+        vein_size: 9,
+        veins_per_chunk: 14,
+    },
+    Ore {
+        cube: blocks::GOLD_ORE,
+        heights: 5..48,
+        vein_size: 8,
+        veins_per_chunk: 3,
+    },
+    Ore {
+        cube: blocks::REDSTONE_ORE,
+        heights: 5..24,
+        vein_size: 7,
+        veins_per_chunk: 6,
+    },
+    Ore {
+        cube: blocks::DIAMOND_ORE,
+        heights: 5..20,
+        vein_size: 5,
+        veins_per_chunk: 1,
+    },
+];
+
+/// Replaces stone with ore veins. Veins are seeded by the chunk alone and cut off at its
+/// border, so the result does not depend on which chunks were generated before.
+pub fn place(chunk: &mut Chunk, seed: u64, chunk_x: i32, chunk_y: i32) {
+    let mut rng = ChunkRng::new(seed, chunk_x, chunk_y, SALT);
+    for ore in ORES {
+        for _ in 0..ore.veins_per_chunk {
+            let mut position = [
+                rng.range(0..CHUNK_SIZE) as i32,
+                rng.range(0..CHUNK_SIZE) as i32,
+                rng.range(ore.heights.clone()) as i32,
+            ];
+            for _ in 0..ore.vein_size {
+                let [x, y, z] = position;
+                if (0..CHUNK_SIZE as i32).contains(&x)
+                    && (0..CHUNK_SIZE as i32).contains(&y)
+                    && ore.heights.contains(&(z as usize))
+                    && chunk.get(x as usize, y as usize, z as usize) == blocks::STONE
+                {
+                    chunk.set(x as usize, y as usize, z as usize, ore.cube);
+                }
+                let axis = rng.range(0..3);
+                position[axis] += if rng.range(0..2) == 0 { -1 } else { 1 };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use map_types::MAP_HEIGHT;
+
+    use super::*;
+
+    fn stone_chunk() -> Chunk {
+        let mut chunk = Chunk::new();
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..MAP_HEIGHT {
+                    chunk.set(x, y, z, blocks::STONE);
+                }
+            }
+        }
+        chunk
+    }
+
+    fn ores_in(chunk: &Chunk) -> Vec<(usize, usize, usize, Cube)> {
+        let mut found = Vec::new();
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..MAP_HEIGHT {
+                    let cube = chunk.get(x, y, z);
+                    if cube != blocks::STONE {
+                        found.push((x, y, z, cube));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn ores_stay_within_their_heights() {
+        for chunk_x in 0..8 {
+            let mut chunk = stone_chunk();
+            place(&mut chunk, 42, chunk_x, -3);
+            let found = ores_in(&chunk);
+            assert!(!found.is_empty());
+            for (_, _, z, cube) in found {
+                let ore = ORES.iter().find(|ore| ore.cube == cube).unwrap();
+                assert!(ore.heights.contains(&z), "{:?} at z = {}", cube, z);
+            }
+        }
+    }
+
+    #[test]
+    fn veins_depend_only_on_seed_and_chunk() {
+        let generate = |seed, chunk_x, chunk_y| {
+            let mut chunk = stone_chunk();
+            place(&mut chunk, seed, chunk_x, chunk_y);
+            ores_in(&chunk)
+        };
+        let first = generate(42, 3, 7);
+        // 다른 청크를 먼저 만들어도 결과가 같아야 함
+        generate(42, 4, 7);
+        assert_eq!(generate(42, 3, 7), first);
+        assert_ne!(generate(42, 4, 7), first);
+        assert_ne!(generate(43, 3, 7), first);
+    }
+
+    #[test]
+    fn only_stone_is_replaced() {
+        let mut chunk = Chunk::new();
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..MAP_HEIGHT {
+                    chunk.set(x, y, z, blocks::DIRT);
+                }
+            }
+        }
+        place(&mut chunk, 42, 0, 0);
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                for z in 0..MAP_HEIGHT {
+                    assert_eq!(chunk.get(x, y, z), blocks::DIRT);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::ops::Range;
+
+/// Deterministic generator for features placed per chunk. Its output depends only on
+/// the world seed, the chunk and the salt, never on the order chunks are generated in.
+pub struct ChunkRng(u64);
+
+impl ChunkRng {
+    /// `salt` keeps different features of the same chunk independent.
+    pub fn new(seed: u64, chunk_x: i32, chunk_y: i32, salt: u64) -> ChunkRng {
+        let mut rng = ChunkRng(seed ^ salt.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        rng.0 ^= rng.next_u64() ^ (chunk_x as u32 as u64);
+        rng.0 ^= rng.next_u64() ^ ((chunk_y as u32 as u64) << 32);
+        rng
+    }
+
+    /// SplitMix64.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `range`, which must not be empty.
+    pub fn range(&mut self, range: Range<usize>) -> usize {
+        range.start + (self.next_u64() % (range.end - range.start) as u64) as usize
+    }
+}
@@ -0,0 +1,230 @@
+//! Buildings laid out as text, one string per layer from the bottom up. Each row of
+//! a layer runs along x and rows follow each other along y; the first row is the
+//! front of the building.
+
+use map_types::{blocks, Chunk, Cube, CHUNK_SIZE, MAP_HEIGHT};
+
+/// Foundations never reach deeper than this below the floor.
+const MAX_FOUNDATION: usize = 8;
+
+#[derive(Clone)]
+pub struct Template {
+    width: usize,
+    depth: usize,
+    height: usize,
+    /// `None` leaves whatever the terrain has there.
+    cubes: Vec<Option<Cube>>,
+}
+
+impl Template {
+    /// `palette` maps the characters of `layers` to cubes; `_` keeps the terrain.
+    fn from_layers(layers: &[&[&str]], palette: &[(char, Cube)]) -> Template {
+        let depth = layers[0].len();
+        let width = layers[0][0].len();
+        let mut cubes = Vec::with_capacity(width * depth * layers.len());
+        for layer in layers {
+            assert_eq!(layer.len(), depth, "layers differ in depth");
+            for row in layer.iter() {
+                assert_eq!(row.len(), width, "rows differ in width");
+                cubes.extend(row.chars().map(|c| {
+                    match c {
+                        '_' => None,
+                        '.' => Some(Cube::EMPTY),
+                        c => Some(
+                            palette
+                                .iter()
+                                .find(|(key, _)| *key == c)
+                                .unwrap_or_else(|| panic!("'{}' is not in the palette", c))
+                                .1,
+                        ),
+                    }
+                }));
+            }
+        }
+        Template {
+            width,
+            depth,
+            height: layers.len(),
+            cubes,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    fn get(&self, x: usize, y: usize, z: usize) -> Option<Cube> {
+        self.cubes[(z * self.depth + y) * self.width + x]
+    }
+
+    /// The same building turned a quarter turn, so that its front faces +x instead of -y.
+    pub fn rotated(&self) -> Template {
+        let mut cubes = Vec::with_capacity(self.cubes.len());
+        for z in 0..self.height {
+            for y in 0..self.width {
+                for x in 0..self.depth {
+                    cubes.push(self.get(y, self.depth - 1 - x, z));
+                }
+            }
+        }
+        Template {
+            width: self.depth,
+            depth: self.width,
+            height: self.height,
+            cubes,
+        }
+    }
+}
+
+/// A template standing at a world position. The bottom layer replaces the ground
+/// at `z - 1`, so the floor inside is at `z`.
+pub struct Placement {
+    pub template: Template,
+    pub x: i32,
+    pub y: i32,
+    pub z: usize,
+}
+
+impl Placement {
+    /// Writes the part of the building that falls into the given chunk, with a
+    /// cobblestone foundation down to the ground below it.
+    pub fn place(&self, chunk: &mut Chunk, chunk_x: i32, chunk_y: i32) {
+        let template = &self.template;
+        for ty in 0..template.depth {
+            for tx in 0..template.width {
+                let x = self.x + tx as i32 - chunk_x * CHUNK_SIZE as i32;
+                let y = self.y + ty as i32 - chunk_y * CHUNK_SIZE as i32;
+                if !(0..CHUNK_SIZE as i32).contains(&x) || !(0..CHUNK_SIZE as i32).contains(&y) {
+                    continue;
+                }
+                let (x, y) = (x as usize, y as usize);
+                let base = self.z - 1;
+                for tz in 0..template.height {
+                    if let Some(cube) = template.get(tx, ty, tz) {
+                        if base + tz < MAP_HEIGHT {
+                            chunk.set(x, y, base + tz, cube);
+                        }
+                    }
+                }
+                if template.get(tx, ty, 0).is_none() {
+                    continue;
+                }
+                for z in (base.saturating_sub(MAX_FOUNDATION)..base).rev() {
+                    if chunk.get(x, y, z).is_solid() {
+                        break;
+                    }
+                    chunk.set(x, y, z, blocks::COBBLESTONE);
+                }
+            }
+        }
+    }
+}
+
+fn house_palette() -> Vec<(char, Cube)> {
+    vec![
+        ('c', blocks::COBBLESTONE),
+        ('p', blocks::OAK_PLANKS),
+        ('l', blocks::OAK_LOG),
+        ('g', blocks::GLASS),
+        ('b', blocks::BOOKSHELF),
+        ('w', blocks::WATER),
+    ]
+}
+
+pub fn house() -> Template {
+    Template::from_layers(
+        &[
+            &["ccccc", "ccccc", "ccccc", "ccccc", "ccccc"],
+            &["lp.pl", "p...p", "p...p", "p...p", "lpppl"],
+            &["lg.gl", "g...g", "p...p", "g...g", "lpgpl"],
+            &["lpppl", "p...p", "p...p", "p...p", "lpppl"],
+            &["ppppp", "ppppp", "ppppp", "ppppp", "ppppp"],
+            &[".....", ".ppp.", ".ppp.", ".ppp.", "....."],
+        ],
+        &house_palette(),
+    )
+}
+
+pub fn library() -> Template {
+    Template::from_layers(
+        &[
+            &[
+                "ccccccc", "ccccccc", "ccccccc", "ccccccc", "ccccccc", "ccccccc",
+            ],
+            &[
+                "lpp.ppl", "pbb.bbp", "p.....p", "p.....p", "pbbbbbp", "lpppppl",
+            ],
+            &[
+                "lgp.pgl", "pbb.bbp", "g.....g", "g.....g", "pbbbbbp", "lpgggpl",
+            ],
+            &[
+                "lpppppl", "p.....p", "p.....p", "p.....p", "p.....p", "lpppppl",
+            ],
+            &[
+                "ccccccc", "ccccccc", "ccccccc", "ccccccc", "ccccccc", "ccccccc",
+            ],
+            &[
+                ".......", ".ccccc.", ".ccccc.", ".ccccc.", ".ccccc.", ".......",
+            ],
+        ],
+        &house_palette(),
+    )
+}
+
+pub fn well() -> Template {
+    Template::from_layers(
+        &[
+            &["cccc", "cwwc", "cwwc", "cccc"],
+            &["cccc", "c..c", "c..c", "cccc"],
+            &["p..p", "....", "....", "p..p"],
+            &["p..p", "....", "....", "p..p"],
+            &["cccc", "cccc", "cccc", "cccc"],
+        ],
+        &house_palette(),
+    )
+}
+
+/// Ripening wheat on both sides of a water channel.
+pub fn farm() -> Template {
+    Template::from_layers(
+        &[
+            &[
+                "lllllll", "ldddddl", "ldddddl", "lwwwwwl", "ldddddl", "ldddddl", "lllllll",
+            ],
+            &[
+                "_______", "_87658_", "_58768_", "_....._", "_46587_", "_78564_", "_______",
+            ],
+        ],
+        &[
+            ('l', blocks::OAK_LOG),
+            ('d', blocks::DIRT),
+            ('w', blocks::WATER),
+            ('4', blocks::WHEAT_4),
+            ('5', blocks::WHEAT_5),
+            ('6', blocks::WHEAT_6),
+            ('7', blocks::WHEAT_7),
+            ('8', blocks::WHEAT_8),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn four_quarter_turns_restore_the_template() {
+        let library = library();
+        let turned = library.rotated();
+        assert_eq!((turned.width, turned.depth), (library.depth, library.width));
+        // 정문이 앞(y = 0)에서 오른쪽 벽(x = 끝)으로 옮겨감
+        assert_eq!(library.get(3, 0, 1), Some(Cube::EMPTY));
+        assert_eq!(turned.get(turned.width - 1, 3, 1), Some(Cube::EMPTY));
+        let back = turned.rotated().rotated().rotated();
+        assert_eq!(back.cubes, library.cubes);
+    }
+}
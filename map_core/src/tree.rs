@@ -0,0 +1,276 @@
+use map_types::{blocks, Biome, Chunk, Cube, RenderClass, CHUNK_SIZE, MAP_HEIGHT};
+
+use crate::random::ChunkRng;
+use crate::water::Lake;
+use crate::Map;
+
+const SALT: u64 = 0x7472_6565;
+
+/// Tree roots tried in every chunk; the biome decides how many of them grow.
+const ATTEMPTS_PER_CHUNK: usize = 8;
+
+/// Percentage of attempts that grow a tree.
+fn chance(biome: Biome) -> usize {
+    match biome {
+        Biome::Forest => 75,
+        Biome::Taiga => 60,
+        Biome::SnowyTundra => 8,
+        Biome::Plains => 4,
+        Biome::Ocean
+        | Biome::Beach
+        | Biome::Desert
+        | Biome::MushroomFields
+        | Biome::River
+        | Biome::Nether => 0,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Kind {
+    Oak,
+    Birch,
+    Spruce,
+    Jungle,
+}
+
+impl Kind {
+    fn log(self) -> Cube {
+        match self {
+            Kind::Oak => blocks::OAK_LOG,
+            Kind::Birch => blocks::BIRCH_LOG,
+            Kind::Spruce => blocks::SPRUCE_LOG,
+            Kind::Jungle => blocks::JUNGLE_LOG,
+        }
+    }
+
+    fn leaves(self) -> Cube {
+        match self {
+            Kind::Oak => blocks::OAK_LEAVES,
+            Kind::Birch => blocks::BIRCH_LEAVES,
+            Kind::Spruce => blocks::SPRUCE_LEAVES,
+            Kind::Jungle => blocks::JUNGLE_LEAVES,
+        }
+    }
+
+    fn trunk_heights(self) -> std::ops::Range<usize> {
+        match self {
+            Kind::Oak => 4..7,
+            Kind::Birch => 5..8,
+            Kind::Spruce => 6..10,
+            Kind::Jungle => 8..13,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Tree {
+    kind: Kind,
+    /// World position of the lowest log, right above the ground.
+    x: i32,
+    y: i32,
+    z: usize,
+    trunk: usize,
+}
+
+impl Tree {
+    /// Every cube of the tree in world coordinates, leaves before logs.
+    fn cubes(&self) -> Vec<(i32, i32, usize, Cube)> {
+        let mut cubes = Vec::new();
+        let top = self.z + self.trunk - 1;
+        let mut layer = |z: usize, radius: i32, rounded: bool| {
+            for dx in -radius..=radius {
+                for dy in -radius..=radius {
+                    let distance = dx * dx + dy * dy;
+                    let limit = if rounded {
+                        radius * radius + 1
+                    } else {
+                        radius * radius
+                    };
+                    if distance <= limit {
+                        cubes.push((self.x + dx, self.y + dy, z, self.kind.leaves()));
+                    }
+                }
+            }
+        };
+        match self.kind {
+            Kind::Oak | Kind::Birch => {
+                layer(top - 2, 2, true);
+                layer(top - 1, 2, true);
+                layer(top, 1, true);
+                layer(top + 1, 1, false);
+            }
+            Kind::Spruce => {
+                // 위로 갈수록 좁아지는 원뿔 모양
+                for z in self.z + 2..=top {
+                    let radius = (((top - z) as i32 + 1) / 2).min(2);
+                    layer(z, radius, true);
+                }
+                layer(top + 1, 0, false);
+            }
+            Kind::Jungle => {
+                layer(top - 1, 3, true);
+                layer(top, 2, true);
+                layer(top + 1, 1, true);
+            }
+        }
+        for z in self.z..=top {
+            cubes.push((self.x, self.y, z, self.kind.log()));
+        }
+        cubes
+    }
+}
+
+fn is_leaves(cube: Cube) -> bool {
+    [Kind::Oak, Kind::Birch, Kind::Spruce, Kind::Jungle]
+        .iter()
+        .any(|kind| kind.leaves() == cube)
+}
+
+/// Trees whose trunk stands in the given chunk.
+fn rooted_in(map: &Map, chunk_x: i32, chunk_y: i32) -> Vec<Tree> {
+    let mut rng = ChunkRng::new(map.seed, chunk_x, chunk_y, SALT);
+    let lakes = Lake::near_chunk(map, chunk_x, chunk_y, 0);
+    let mut trees = Vec::new();
+    for _ in 0..ATTEMPTS_PER_CHUNK {
+        let x = chunk_x * CHUNK_SIZE as i32 + rng.range(0..CHUNK_SIZE) as i32;
+        let y = chunk_y * CHUNK_SIZE as i32 + rng.range(0..CHUNK_SIZE) as i32;
+        let roll = rng.range(0..100);
+        let pick = rng.range(0..100);
+        let trunk_roll = rng.range(0..100);
+
+        let column = map.column(x as f32, y as f32, &lakes);
+        if roll >= chance(column.biome)
+            || column.height <= column.water
+            || column.bank
+            || !map
+                .caves
+                .is_sealed(x as f32, y as f32, column.height, column.water)
+        {
+            continue;
+        }
+        let height = column.height;
+        let kind = match column.biome {
+            Biome::Forest if map.climate(x as f32, y as f32).temperature > 0.08 => Kind::Jungle,
+            Biome::Forest if pick < 30 => Kind::Birch,
+            Biome::Taiga | Biome::SnowyTundra => Kind::Spruce,
+            _ => Kind::Oak,
+        };
+        let heights = kind.trunk_heights();
+        let trunk = heights.start + trunk_roll % heights.len();
+        if height + trunk + 2 >= MAP_HEIGHT {
+            continue;
+        }
+        trees.push(Tree {
+            kind,
+            x,
+            y,
+            z: height,
+            trunk,
+        });
+    }
+    trees
+}
+
+/// Grows the trees of this chunk and of its neighbours into `chunk`, so canopies
+/// crossing the border end up in both chunks whichever is generated first.
+pub fn place(map: &Map, chunk: &mut Chunk, chunk_x: i32, chunk_y: i32) {
+    let x_offset = chunk_x * CHUNK_SIZE as i32;
+    let y_offset = chunk_y * CHUNK_SIZE as i32;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for tree in rooted_in(map, chunk_x + dx, chunk_y + dy) {
+                for (x, y, z, cube) in tree.cubes() {
+                    let (x, y) = (x - x_offset, y - y_offset);
+                    if !(0..CHUNK_SIZE as i32).contains(&x) || !(0..CHUNK_SIZE as i32).contains(&y)
+                    {
+                        continue;
+                    }
+                    let (x, y) = (x as usize, y as usize);
+                    let current = chunk.get(x, y, z);
+                    // 통나무는 나뭇잎을 덮어쓰고, 나뭇잎은 빈 칸과 풀만 채움
+                    let replaceable = current == Cube::EMPTY
+                        || current.block().render == RenderClass::Plantlike
+                        || (cube == tree.kind.log() && is_leaves(current));
+                    if replaceable {
+                        chunk.set(x, y, z, cube);
+                    }
+                }
+                let (x, y) = (tree.x - x_offset, tree.y - y_offset);
+                if (0..CHUNK_SIZE as i32).contains(&x) && (0..CHUNK_SIZE as i32).contains(&y) {
+                    chunk.set(x as usize, y as usize, tree.z - 1, blocks::DIRT);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canopies_cross_chunk_borders() {
+        let map = Map::new(42);
+        // 동쪽 경계에 붙어 있는 나무를 찾아 옆 청크에 나뭇잎이 들어갔는지 확인
+        let (chunk_x, chunk_y, tree) = (0..64)
+            .flat_map(|chunk_x| (0..8).map(move |chunk_y| (chunk_x, chunk_y)))
+            .find_map(|(chunk_x, chunk_y)| {
+                rooted_in(&map, chunk_x, chunk_y)
+                    .into_iter()
+                    .find(|tree| tree.x.rem_euclid(CHUNK_SIZE as i32) == CHUNK_SIZE as i32 - 1)
+                    .map(|tree| (chunk_x, chunk_y, tree))
+            })
+            .expect("no tree next to a chunk border");
+
+        let neighbor = map.get_chunk(chunk_x + 1, chunk_y);
+        let leaves_in_neighbor = tree
+            .cubes()
+            .into_iter()
+            .filter(|&(x, _, _, cube)| {
+                x == (chunk_x + 1) * CHUNK_SIZE as i32 && cube == tree.kind.leaves()
+            })
+            .filter(|&(_, y, z, _)| {
+                let y = y - chunk_y * CHUNK_SIZE as i32;
+                (0..CHUNK_SIZE as i32).contains(&y)
+                    && neighbor.get(0, y as usize, z) == tree.kind.leaves()
+            })
+            .count();
+        assert!(
+            leaves_in_neighbor > 0,
+            "{:?} left no leaves across the border",
+            tree
+        );
+
+        // 어느 청크를 먼저 만들든 결과가 같음
+        let own = map.get_chunk(chunk_x, chunk_y);
+        let fresh = Map::new(42);
+        assert_eq!(fresh.get_chunk(chunk_x + 1, chunk_y), neighbor);
+        assert_eq!(fresh.get_chunk(chunk_x, chunk_y), own);
+    }
+
+    #[test]
+    fn trunks_stand_on_dirt() {
+        let map = Map::new(7);
+        // 나무가 있는 청크를 찾을 때까지 훑음
+        let mut checked = 0;
+        let chunks = (0..64).flat_map(|chunk_x| (0..64).map(move |chunk_y| (chunk_x, chunk_y)));
+        for (chunk_x, chunk_y) in chunks {
+            let trees = rooted_in(&map, chunk_x, chunk_y);
+            if trees.is_empty() {
+                continue;
+            }
+            let chunk = map.get_chunk(chunk_x, chunk_y);
+            for tree in trees {
+                let x = tree.x.rem_euclid(CHUNK_SIZE as i32) as usize;
+                let y = tree.y.rem_euclid(CHUNK_SIZE as i32) as usize;
+                assert_eq!(chunk.get(x, y, tree.z - 1), blocks::DIRT);
+                assert_eq!(chunk.get(x, y, tree.z), tree.kind.log());
+                checked += 1;
+            }
+            if checked >= 20 {
+                return;
+            }
+        }
+        panic!("only {} trees in 64x64 chunks", checked);
+    }
+}
@@ -0,0 +1,209 @@
+use map_types::{blocks, Biome, Chunk, Cube, RenderClass, CHUNK_SIZE, MAP_HEIGHT};
+
+use crate::random::ChunkRng;
+use crate::structure::{self, Placement, Template};
+use crate::water::Lake;
+use crate::Map;
+
+/// Each village sits inside one square cell of this size.
+const VILLAGE_CELL: i32 = 256;
+const SALT: u64 = 0x7669_6c6c;
+/// One in this many cells tries to hold a village.
+const VILLAGE_RARITY: usize = 2;
+/// Distance between the centres of neighbouring plots; plots form a 3x3 grid.
+const PLOT: i32 = 12;
+/// Half the size of the square a village covers.
+const EXTENT: i32 = PLOT + PLOT / 2 + 1;
+/// Largest height difference between plots that still counts as flat.
+const MAX_SLOPE: usize = 4;
+
+fn is_suitable(biome: Biome) -> bool {
+    matches!(
+        biome,
+        Biome::Plains | Biome::Desert | Biome::Taiga | Biome::SnowyTundra
+    )
+}
+
+pub struct Village {
+    x: i32,
+    y: i32,
+    buildings: Vec<Placement>,
+}
+
+impl Village {
+    /// The village of a cell, if it has one and the land there is flat and dry.
+    fn in_cell(map: &Map, cell_x: i32, cell_y: i32) -> Option<Village> {
+        let mut rng = ChunkRng::new(map.seed, cell_x, cell_y, SALT);
+        let exists = rng.range(0..VILLAGE_RARITY) == 0;
+        let margin = EXTENT as usize + 1;
+        let x = cell_x * VILLAGE_CELL + rng.range(margin..VILLAGE_CELL as usize - margin) as i32;
+        let y = cell_y * VILLAGE_CELL + rng.range(margin..VILLAGE_CELL as usize - margin) as i32;
+        if !exists {
+            return None;
+        }
+
+        let lakes = Lake::near(map, x - EXTENT, y - EXTENT, x + EXTENT, y + EXTENT);
+        let mut plots = Vec::with_capacity(9);
+        for j in -1..=1 {
+            for i in -1..=1 {
+                let (plot_x, plot_y) = (x + i * PLOT, y + j * PLOT);
+                let column = map.column(plot_x as f32, plot_y as f32, &lakes);
+                if !is_suitable(column.biome) || column.height <= column.water + 1 || column.bank {
+                    return None;
+                }
+                plots.push((i, j, column.height));
+            }
+        }
+        let lowest = plots.iter().map(|&(.., height)| height).min()?;
+        let highest = plots.iter().map(|&(.., height)| height).max()?;
+        if highest - lowest > MAX_SLOPE {
+            return None;
+        }
+
+        let buildings = plots
+            .into_iter()
+            .filter_map(|(i, j, height)| {
+                let roll = rng.range(0..100);
+                let template = match (i, j) {
+                    (0, 0) => structure::well(),
+                    _ if roll < 45 => structure::house(),
+                    _ if roll < 60 => structure::library(),
+                    _ if roll < 90 => structure::farm(),
+                    _ => return None,
+                };
+                // 문이 마을 가운데를 향하도록 돌림
+                let turns = match (i, j) {
+                    (_, -1) => 2,
+                    (-1, 0) => 1,
+                    (1, 0) => 3,
+                    _ => 0,
+                };
+                let template =
+                    (0..turns).fold(template, |template: Template, _| template.rotated());
+                Some(Placement {
+                    x: x + i * PLOT - template.width() as i32 / 2,
+                    y: y + j * PLOT - template.depth() as i32 / 2,
+                    z: height,
+                    template,
+                })
+            })
+            .collect();
+        Some(Village { x, y, buildings })
+    }
+
+    /// Villages that can reach into the given chunk.
+    fn near_chunk(map: &Map, chunk_x: i32, chunk_y: i32) -> Vec<Village> {
+        let size = CHUNK_SIZE as i32;
+        let cells = |offset: i32| {
+            (offset * size).div_euclid(VILLAGE_CELL)
+                ..=(offset * size + size - 1).div_euclid(VILLAGE_CELL)
+        };
+        let mut villages = Vec::new();
+        for cell_x in cells(chunk_x) {
+            for cell_y in cells(chunk_y) {
+                villages.extend(Village::in_cell(map, cell_x, cell_y));
+            }
+        }
+        villages
+    }
+
+    /// Whether the world column (x, y) is part of the gravel lanes between plots.
+    fn is_path(&self, x: i32, y: i32) -> bool {
+        let (dx, dy) = (x - self.x, y - self.y);
+        let lane = |d: i32| (d.abs() - PLOT / 2).abs() <= 1;
+        dx.abs() <= EXTENT && dy.abs() <= EXTENT && (lane(dx) || lane(dy))
+    }
+
+    /// Turns the top of a path column into gravel and clears plants on it.
+    fn pave(chunk: &mut Chunk, x: usize, y: usize) {
+        for z in (0..MAP_HEIGHT).rev() {
+            let cube = chunk.get(x, y, z);
+            if cube.is_solid() {
+                if [
+                    blocks::GRASS_BLOCK,
+                    blocks::SNOWY_GRASS_BLOCK,
+                    blocks::DIRT,
+                    blocks::SAND,
+                ]
+                .contains(&cube)
+                {
+                    chunk.set(x, y, z, blocks::GRAVEL);
+                }
+                return;
+            }
+            if cube.fluid().is_some() {
+                return;
+            }
+            if cube.block().render == RenderClass::Plantlike {
+                chunk.set(x, y, z, Cube::EMPTY);
+            }
+        }
+    }
+}
+
+/// Builds the parts of nearby villages that fall into `chunk`.
+pub fn place(map: &Map, chunk: &mut Chunk, chunk_x: i32, chunk_y: i32) {
+    let x_offset = chunk_x * CHUNK_SIZE as i32;
+    let y_offset = chunk_y * CHUNK_SIZE as i32;
+    for village in Village::near_chunk(map, chunk_x, chunk_y) {
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                if village.is_path(x_offset + x as i32, y_offset + y as i32) {
+                    Village::pave(chunk, x, y);
+                }
+            }
+        }
+        for building in &village.buildings {
+            building.place(chunk, chunk_x, chunk_y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn villages_span_chunks_deterministically() {
+        let map = Map::new(42);
+        let village = (0..16)
+            .flat_map(|cell_x| (0..16).map(move |cell_y| (cell_x, cell_y)))
+            .find_map(|(cell_x, cell_y)| Village::in_cell(&map, cell_x, cell_y))
+            .expect("no village at all");
+        assert!(!village.buildings.is_empty());
+
+        // 여러 청크에 걸쳐 지어짐
+        let size = CHUNK_SIZE as i32;
+        let mut chunks: Vec<(i32, i32)> = village
+            .buildings
+            .iter()
+            .flat_map(|building| {
+                let far_x = building.x + building.template.width() as i32 - 1;
+                let far_y = building.y + building.template.depth() as i32 - 1;
+                [(building.x, building.y), (far_x, far_y)]
+            })
+            .map(|(x, y)| (x.div_euclid(size), y.div_euclid(size)))
+            .collect();
+        chunks.sort();
+        chunks.dedup();
+        assert!(chunks.len() > 1, "village fits into a single chunk");
+
+        let well = village
+            .buildings
+            .iter()
+            .find(|building| {
+                (building.x..building.x + building.template.width() as i32).contains(&village.x)
+                    && (building.y..building.y + building.template.depth() as i32)
+                        .contains(&village.y)
+            })
+            .unwrap();
+        let (chunk_x, chunk_y) = (well.x.div_euclid(size), well.y.div_euclid(size));
+        let chunk = map.get_chunk(chunk_x, chunk_y);
+        let (x, y) = (
+            well.x.rem_euclid(size) as usize,
+            well.y.rem_euclid(size) as usize,
+        );
+        assert_eq!(chunk.get(x, y, well.z - 1), blocks::COBBLESTONE);
+        assert_eq!(Map::new(42).get_chunk(chunk_x, chunk_y), chunk);
+    }
+}
@@ -0,0 +1,187 @@
+use map_types::CHUNK_SIZE;
+use noise::{Noise, NoiseLayer};
+
+use crate::random::ChunkRng;
+use crate::{Map, WATER_LEVEL};
+
+/// River channels follow the zero line of a noise, so `RIVER_WIDTH` and
+/// `VALLEY_WIDTH` are in noise units: roughly 6 and 25 blocks to either side.
+const RIVER_WIDTH: f32 = 0.012;
+const VALLEY_WIDTH: f32 = 0.05;
+const RIVER_DEPTH: f32 = 4.0;
+
+/// Each lake sits inside one square cell of this size, so a column only has to
+/// look at the lake of its own cell.
+const LAKE_CELL: i32 = 64;
+const LAKE_SALT: u64 = 0x6c61_6b65;
+/// One in this many cells holds a lake.
+const LAKE_RARITY: usize = 3;
+const LAKE_RADII: std::ops::Range<usize> = 6..15;
+/// Sand ring around a lake.
+const BANK_WIDTH: f32 = 3.0;
+/// Ground samples around a lake that its surface must stay below.
+const RIM_SAMPLES: usize = 16;
+
+#[derive(Clone)]
+pub struct Rivers {
+    noise: Noise,
+}
+
+impl Rivers {
+    pub fn new(seed: u64) -> Rivers {
+        Rivers {
+            noise: Noise::new(&[NoiseLayer::new(0.0025, 1.0)], seed.wrapping_add(5)),
+        }
+    }
+
+    /// Lowers `height` into a valley that ends in a channel below sea level, and
+    /// tells whether (x, y) lies inside the channel.
+    pub fn carve(&self, x: f32, y: f32, height: usize) -> (usize, bool) {
+        let distance = self.noise.noise2(x, y).abs();
+        if distance >= VALLEY_WIDTH {
+            return (height, false);
+        }
+        if distance < RIVER_WIDTH {
+            let depth = 1.0 + RIVER_DEPTH * (1.0 - distance / RIVER_WIDTH);
+            let bed = WATER_LEVEL as f32 - depth;
+            return ((height as f32).min(bed) as usize, true);
+        }
+        let t = 1.0 - (distance - RIVER_WIDTH) / (VALLEY_WIDTH - RIVER_WIDTH);
+        let weight = t * t * (3.0 - 2.0 * t);
+        let bank = (WATER_LEVEL + 1) as f32;
+        let lowered = height as f32 + weight * (bank - height as f32);
+        ((height as f32).min(lowered) as usize, false)
+    }
+}
+
+/// A round lake above sea level.
+pub struct Lake {
+    x: f32,
+    y: f32,
+    radius: f32,
+    /// Height of the water surface.
+    level: usize,
+}
+
+impl Lake {
+    /// The lake of a cell, if it has one. Its surface stays below the ground around it,
+    /// so it never spills over its rim.
+    fn in_cell(map: &Map, cell_x: i32, cell_y: i32) -> Option<Lake> {
+        let mut rng = ChunkRng::new(map.seed, cell_x, cell_y, LAKE_SALT);
+        let exists = rng.range(0..LAKE_RARITY) == 0;
+        let radius = rng.range(LAKE_RADII) as f32;
+        // 둑까지 셀 안에 들어오도록 중심을 잡음
+        let margin = (radius + BANK_WIDTH).ceil() as usize + 1;
+        let x = (cell_x * LAKE_CELL) as f32 + rng.range(margin..LAKE_CELL as usize - margin) as f32;
+        let y = (cell_y * LAKE_CELL) as f32 + rng.range(margin..LAKE_CELL as usize - margin) as f32;
+        if !exists {
+            return None;
+        }
+
+        let mut rim = usize::MAX;
+        for i in 0..RIM_SAMPLES {
+            let angle = i as f32 / RIM_SAMPLES as f32 * std::f32::consts::TAU;
+            let (ground, _) = map.terrain(
+                x + angle.cos() * (radius + 1.0),
+                y + angle.sin() * (radius + 1.0),
+            );
+            rim = rim.min(ground);
+        }
+        let level = rim - 1;
+        if level <= WATER_LEVEL + 2 {
+            return None;
+        }
+        Some(Lake {
+            x,
+            y,
+            radius,
+            level,
+        })
+    }
+
+    /// Lakes whose cells overlap the area from (x_min, y_min) to (x_max, y_max), inclusive.
+    pub fn near(map: &Map, x_min: i32, y_min: i32, x_max: i32, y_max: i32) -> Vec<Lake> {
+        let mut lakes = Vec::new();
+        for cell_x in x_min.div_euclid(LAKE_CELL)..=x_max.div_euclid(LAKE_CELL) {
+            for cell_y in y_min.div_euclid(LAKE_CELL)..=y_max.div_euclid(LAKE_CELL) {
+                lakes.extend(Lake::in_cell(map, cell_x, cell_y));
+            }
+        }
+        lakes
+    }
+
+    /// Lakes that can reach into the given chunk or `margin` columns around it.
+    pub fn near_chunk(map: &Map, chunk_x: i32, chunk_y: i32, margin: i32) -> Vec<Lake> {
+        let size = CHUNK_SIZE as i32;
+        Lake::near(
+            map,
+            chunk_x * size - margin,
+            chunk_y * size - margin,
+            chunk_x * size + size - 1 + margin,
+            chunk_y * size + size - 1 + margin,
+        )
+    }
+
+    /// Bowl-shaped bed under the lake at (x, y) and its water level, or whether
+    /// (x, y) is on its bank.
+    pub fn shape(&self, x: f32, y: f32) -> LakeShape {
+        let distance = ((x - self.x).powi(2) + (y - self.y).powi(2)).sqrt();
+        if distance < self.radius {
+            let depth = 1.0 + 4.0 * (1.0 - (distance / self.radius).powi(2));
+            LakeShape::Water {
+                bed: self.level - depth as usize,
+                level: self.level,
+            }
+        } else if distance < self.radius + BANK_WIDTH {
+            LakeShape::Bank
+        } else {
+            LakeShape::Outside
+        }
+    }
+}
+
+pub enum LakeShape {
+    Water { bed: usize, level: usize },
+    Bank,
+    Outside,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn river_channels_end_below_sea_level() {
+        let rivers = Rivers::new(42);
+        let mut channels = 0;
+        for x in 0..400 {
+            for y in 0..400 {
+                let (x, y) = (x as f32 * 2.0, y as f32 * 2.0);
+                for height in [WATER_LEVEL + 1, 150, 220] {
+                    let (carved, channel) = rivers.carve(x, y, height);
+                    assert!(carved <= height);
+                    if channel {
+                        assert!(carved < WATER_LEVEL);
+                        channels += 1;
+                    }
+                }
+            }
+        }
+        assert!(channels > 0);
+    }
+
+    #[test]
+    fn lakes_hold_water_above_sea_level() {
+        let map = Map::new(42);
+        let lakes = Lake::near(&map, 0, 0, 1023, 1023);
+        assert!(!lakes.is_empty());
+        for lake in &lakes {
+            assert!(lake.level > WATER_LEVEL + 2);
+            let center = map.column(lake.x, lake.y, &lakes);
+            assert_eq!(center.water, lake.level);
+            assert!(center.height < lake.level);
+            let shore = map.column(lake.x + lake.radius + 1.0, lake.y, &lakes);
+            assert!(shore.bank || shore.height <= shore.water);
+        }
+    }
+}
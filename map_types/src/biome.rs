@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+/// Climate zone of one column. Stored by variant index, so new biomes must be appended.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, Serialize, Deserialize)]
+pub enum Biome {
+    Ocean,
+    Beach,
+    #[default]
+    Plains,
+    Desert,
+    Forest,
+    Taiga,
+    SnowyTundra,
+    MushroomFields,
+    River,
+    Nether,
+}
+
+impl Biome {
+    pub const ALL: &'static [Biome] = &[
+        Biome::Ocean,
+        Biome::Beach,
+        Biome::Plains,
+        Biome::Desert,
+        Biome::Forest,
+        Biome::Taiga,
+        Biome::SnowyTundra,
+        Biome::MushroomFields,
+        Biome::River,
+        Biome::Nether,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Biome::Ocean => "Ocean",
+            Biome::Beach => "Beach",
+            Biome::Plains => "Plains",
+            Biome::Desert => "Desert",
+            Biome::Forest => "Forest",
+            Biome::Taiga => "Taiga",
+            Biome::SnowyTundra => "Snowy Tundra",
+            Biome::MushroomFields => "Mushroom Fields",
+            Biome::River => "River",
+            Biome::Nether => "Nether",
+        }
+    }
+}
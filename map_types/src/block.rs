@@ -0,0 +1,471 @@
+//! Block definitions and per-cube gameplay properties.
+//!
+//! Blocks are described in `blocks.ron` (see the comment at its top). The
+//! file is embedded at compile time and can be replaced at startup with
+//! `load_definitions`, so adding a block only takes a new entry there. The
+//! mesher, the server and anything else that needs to know how a cube
+//! behaves asks the registry through `Cube::block` / `Cube::properties`.
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+
+use crate::{blocks, Cube};
+
+const DEFAULT_DEFINITIONS: &str = include_str!("../blocks.ron");
+
+/// Environment variable naming a definitions file to use instead of the built-in one.
+pub const DEFINITIONS_ENV: &str = "VOX_BLOCKS";
+
+/// Which mesher path draws the cube.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum RenderClass {
+    Invisible,
+    Solid,
+    Translucent,
+    /// Solid cube with a biome-tinted overlay, e.g. grass blocks.
+    FilteredSolid,
+    /// Two crossed quads.
+    Plantlike,
+    /// Four quads in a hash pattern, e.g. crops.
+    Harvestable,
+    Cactus,
+    /// Tinted translucent volume whose surface drops as it flows, e.g. water.
+    Fluid,
+}
+
+/// How far a fluid cube is from its source; see the comment in `blocks.ron`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum FluidLevel {
+    Source,
+    /// 1 right next to a source up to `FluidLevel::MAX_FLOW` at the end of the flow.
+    Flowing(u8),
+}
+
+impl FluidLevel {
+    pub const MAX_FLOW: u8 = 7;
+
+    /// Height of the surface within the cube, when no fluid lies on top of it.
+    pub fn height(&self) -> f32 {
+        match *self {
+            FluidLevel::Source => 8.0 / 9.0,
+            FluidLevel::Flowing(level) => (8 - level) as f32 / 9.0,
+        }
+    }
+}
+
+/// How a cube is drawn and whether it hides the faces of its neighbours.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Transparency {
+    /// Nothing is drawn.
+    Invisible,
+    /// Drawn in the opaque pass and hides every neighbouring face.
+    Opaque,
+    /// Drawn in the translucent pass and hides faces of opaque or translucent neighbours.
+    Translucent,
+    /// Drawn in the translucent pass with holes; never hides neighbouring faces.
+    Cutout,
+}
+
+/// Atlas tiles as (row, column) in the texture sheet.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Deserialize)]
+pub enum Tiles {
+    Same((u32, u32)),
+    Sides {
+        side: (u32, u32),
+        top: (u32, u32),
+        bottom: (u32, u32),
+    },
+}
+
+impl Tiles {
+    fn side(&self) -> (f32, f32) {
+        match *self {
+            Tiles::Same((y, x)) | Tiles::Sides { side: (y, x), .. } => (x as f32, y as f32),
+        }
+    }
+
+    fn top(&self) -> (f32, f32) {
+        match *self {
+            Tiles::Same((y, x)) | Tiles::Sides { top: (y, x), .. } => (x as f32, y as f32),
+        }
+    }
+
+    fn bottom(&self) -> (f32, f32) {
+        match *self {
+            Tiles::Same((y, x)) | Tiles::Sides { bottom: (y, x), .. } => (x as f32, y as f32),
+        }
+    }
+
+    /// Texture coordinates for quads that are not tied to a face.
+    pub fn tex_coord(&self) -> [[f32; 2]; 4] {
+        self.tex_coord_px()
+    }
+
+    pub fn tex_coord_px(&self) -> [[f32; 2]; 4] {
+        let (x, y) = self.side();
+        [[x + 1.0, y + 1.0], [x, y + 1.0], [x, y], [x + 1.0, y]]
+    }
+
+    pub fn tex_coord_nx(&self) -> [[f32; 2]; 4] {
+        let (x, y) = self.side();
+        [[x + 1.0, y], [x, y], [x, y + 1.0], [x + 1.0, y + 1.0]]
+    }
+
+    pub fn tex_coord_py(&self) -> [[f32; 2]; 4] {
+        let (x, y) = self.side();
+        [[x, y + 1.0], [x + 1.0, y + 1.0], [x + 1.0, y], [x, y]]
+    }
+
+    pub fn tex_coord_ny(&self) -> [[f32; 2]; 4] {
+        let (x, y) = self.side();
+        [[x, y], [x + 1.0, y], [x + 1.0, y + 1.0], [x, y + 1.0]]
+    }
+
+    pub fn tex_coord_pz(&self) -> [[f32; 2]; 4] {
+        let (x, y) = self.top();
+        [[x + 1.0, y + 1.0], [x, y + 1.0], [x, y], [x + 1.0, y]]
+    }
+
+    pub fn tex_coord_nz(&self) -> [[f32; 2]; 4] {
+        let (x, y) = self.bottom();
+        [[x + 1.0, y + 1.0], [x, y + 1.0], [x, y], [x + 1.0, y]]
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BlockProperties {
+    pub display_name: String,
+    /// Seconds to break by hand, or `None` if it cannot be broken.
+    pub hardness: Option<f32>,
+    pub transparency: Transparency,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Block {
+    pub name: String,
+    pub render: RenderClass,
+    /// Present for every render class except `Invisible`.
+    pub tiles: Option<Tiles>,
+    /// Overlay tiles of a `FilteredSolid` or `Fluid`.
+    pub filter_tiles: Option<Tiles>,
+    /// Present exactly for `Fluid` blocks.
+    pub fluid: Option<FluidLevel>,
+    pub properties: BlockProperties,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BlockDefinition {
+    id: u16,
+    name: String,
+    display_name: String,
+    render: RenderClass,
+    #[serde(default)]
+    tiles: Option<Tiles>,
+    #[serde(default)]
+    filter_tiles: Option<Tiles>,
+    #[serde(default)]
+    fluid: Option<FluidLevel>,
+    hardness: Option<f32>,
+}
+
+#[derive(Debug)]
+pub enum DefinitionError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+    AlreadyLoaded,
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefinitionError::Io(e) => write!(f, "failed to read block definitions: {}", e),
+            DefinitionError::Parse(e) => write!(f, "failed to parse block definitions: {}", e),
+            DefinitionError::Invalid(detail) => write!(f, "invalid block definitions: {}", detail),
+            DefinitionError::AlreadyLoaded => write!(f, "block definitions are already in use"),
+        }
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+pub struct BlockRegistry {
+    /// Indexed by id; ids that are not defined are `None`.
+    blocks: Vec<Option<Block>>,
+    by_name: HashMap<String, Cube>,
+    fingerprint: u64,
+}
+
+impl BlockRegistry {
+    pub fn parse(source: &str) -> Result<Self, DefinitionError> {
+        // Optional fields are written without `Some(..)`.
+        let definitions: Vec<BlockDefinition> = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(source)
+            .map_err(DefinitionError::Parse)?;
+        let invalid = |detail: String| Err(DefinitionError::Invalid(detail));
+
+        let mut by_name = HashMap::new();
+        for definition in &definitions {
+            if by_name
+                .insert(definition.name.clone(), Cube(definition.id))
+                .is_some()
+            {
+                return invalid(format!("name {:?} is defined twice", definition.name));
+            }
+        }
+
+        let size = definitions
+            .iter()
+            .map(|d| d.id as usize + 1)
+            .max()
+            .unwrap_or(0);
+        let mut blocks: Vec<Option<Block>> = vec![None; size];
+        for definition in definitions {
+            let id = definition.id as usize;
+            if blocks[id].is_some() {
+                return invalid(format!("id {} is defined twice", id));
+            }
+            let needs_tiles = definition.render != RenderClass::Invisible;
+            if needs_tiles && definition.tiles.is_none() {
+                return invalid(format!("{} has no tiles", definition.name));
+            }
+            let tinted = matches!(
+                definition.render,
+                RenderClass::FilteredSolid | RenderClass::Fluid
+            );
+            if tinted != definition.filter_tiles.is_some() {
+                return invalid(format!(
+                    "{}: filter_tiles must be given exactly for FilteredSolid and Fluid blocks",
+                    definition.name
+                ));
+            }
+            match definition.fluid {
+                Some(FluidLevel::Flowing(level))
+                    if !(1..=FluidLevel::MAX_FLOW).contains(&level) =>
+                {
+                    return invalid(format!(
+                        "{}: flowing level must be 1 to {}",
+                        definition.name,
+                        FluidLevel::MAX_FLOW
+                    ));
+                }
+                fluid if fluid.is_some() != (definition.render == RenderClass::Fluid) => {
+                    return invalid(format!(
+                        "{}: fluid must be given exactly for Fluid blocks",
+                        definition.name
+                    ));
+                }
+                _ => {}
+            }
+            let transparency = match definition.render {
+                RenderClass::Invisible => Transparency::Invisible,
+                RenderClass::Solid | RenderClass::FilteredSolid => Transparency::Opaque,
+                RenderClass::Translucent | RenderClass::Fluid => Transparency::Translucent,
+                RenderClass::Plantlike | RenderClass::Harvestable | RenderClass::Cactus => {
+                    Transparency::Cutout
+                }
+            };
+            blocks[id] = Some(Block {
+                name: definition.name,
+                render: definition.render,
+                tiles: definition.tiles,
+                filter_tiles: definition.filter_tiles,
+                fluid: definition.fluid,
+                properties: BlockProperties {
+                    display_name: definition.display_name,
+                    hardness: definition.hardness,
+                    transparency,
+                },
+            });
+        }
+
+        match blocks.first() {
+            Some(Some(air)) if air.render == RenderClass::Invisible => {}
+            _ => return invalid("id 0 must be an Invisible block".to_string()),
+        }
+        for (cube, name) in blocks::KNOWN {
+            if by_name.get(*name) != Some(cube) {
+                return invalid(format!("{:?} must keep id {}", name, cube.0));
+            }
+        }
+
+        Ok(BlockRegistry {
+            fingerprint: fingerprint(&blocks),
+            blocks,
+            by_name,
+        })
+    }
+
+    pub fn get(&self, cube: Cube) -> Option<&Block> {
+        self.blocks.get(cube.0 as usize)?.as_ref()
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<Cube> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Every defined cube in id order.
+    pub fn cubes(&self) -> impl Iterator<Item = Cube> + '_ {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.is_some())
+            .map(|(id, _)| Cube(id as u16))
+    }
+
+    /// Hash of the definitions, so peers can check they agree on them.
+    /// Comments, formatting and field order in the file make no difference.
+    pub fn fingerprint(&self) -> u64 {
+        self.fingerprint
+    }
+}
+
+/// 64-bit FNV-1a of every block in id order; stable across builds, unlike
+/// `DefaultHasher`.
+fn fingerprint(blocks: &[Option<Block>]) -> u64 {
+    let mut canonical = String::new();
+    for (id, block) in blocks.iter().enumerate() {
+        if let Some(block) = block {
+            writeln!(canonical, "{} {:?}", id, block).unwrap();
+        }
+    }
+    canonical.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+static REGISTRY: OnceLock<BlockRegistry> = OnceLock::new();
+
+/// Replaces the built-in definitions with the file at `path`.
+/// Must be called before any cube is looked up.
+pub fn load_definitions(path: impl AsRef<Path>) -> Result<(), DefinitionError> {
+    let source = std::fs::read_to_string(path).map_err(DefinitionError::Io)?;
+    let registry = BlockRegistry::parse(&source)?;
+    REGISTRY
+        .set(registry)
+        .map_err(|_| DefinitionError::AlreadyLoaded)
+}
+
+pub fn registry() -> &'static BlockRegistry {
+    REGISTRY.get_or_init(|| {
+        BlockRegistry::parse(DEFAULT_DEFINITIONS).expect("built-in blocks.ron is invalid")
+    })
+}
+
+impl Cube {
+    /// Panics if the id is not defined; check `is_defined` for ids from the outside.
+    pub fn block(&self) -> &'static Block {
+        registry()
+            .get(*self)
+            .unwrap_or_else(|| panic!("undefined block id {}", self.0))
+    }
+
+    pub fn properties(&self) -> &'static BlockProperties {
+        &self.block().properties
+    }
+
+    pub fn is_defined(&self) -> bool {
+        registry().get(*self).is_some()
+    }
+
+    pub fn fluid(&self) -> Option<FluidLevel> {
+        self.block().fluid
+    }
+
+    /// Shorthand for checking that `properties().hardness` is set.
+    pub fn is_breakable(&self) -> bool {
+        self.properties().hardness.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_definitions_are_valid() {
+        let registry = BlockRegistry::parse(DEFAULT_DEFINITIONS).unwrap();
+        assert_eq!(registry.by_name("stone"), Some(blocks::STONE));
+        assert!(registry
+            .get(blocks::BEDROCK)
+            .unwrap()
+            .properties
+            .hardness
+            .is_none());
+    }
+
+    #[test]
+    fn render_class_decides_occlusion() {
+        for cube in registry().cubes() {
+            let render = cube.block().render;
+            assert_eq!(
+                cube.is_solid(),
+                matches!(render, RenderClass::Solid | RenderClass::FilteredSolid)
+            );
+            assert_eq!(
+                cube.is_translucent_or_solid(),
+                matches!(
+                    render,
+                    RenderClass::Solid
+                        | RenderClass::FilteredSolid
+                        | RenderClass::Translucent
+                        | RenderClass::Fluid
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn water_levels_map_to_their_blocks() {
+        assert_eq!(blocks::WATER.fluid(), Some(FluidLevel::Source));
+        for level in 1..=FluidLevel::MAX_FLOW {
+            let water = blocks::water(FluidLevel::Flowing(level));
+            assert_eq!(water.fluid(), Some(FluidLevel::Flowing(level)));
+            assert!(!water.is_solid() && !water.is_breakable());
+        }
+        assert!(FluidLevel::Flowing(FluidLevel::MAX_FLOW).height() > 0.0);
+        assert!(FluidLevel::Source.height() < 1.0);
+    }
+
+    #[test]
+    fn broken_definitions_are_rejected() {
+        let duplicate_id = r#"[
+            (id: 0, name: "air", display_name: "Air", render: Invisible, hardness: None),
+            (id: 0, name: "void", display_name: "Void", render: Invisible, hardness: None),
+        ]"#;
+        assert!(matches!(
+            BlockRegistry::parse(duplicate_id),
+            Err(DefinitionError::Invalid(_))
+        ));
+
+        // Ids the code relies on must not move.
+        let moved_stone = DEFAULT_DEFINITIONS.replace("id: 3,", "id: 300,");
+        assert!(matches!(
+            BlockRegistry::parse(&moved_stone),
+            Err(DefinitionError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn fingerprint_ignores_how_the_file_is_written() {
+        let fingerprint = |source: &str| BlockRegistry::parse(source).unwrap().fingerprint();
+        let original = fingerprint(DEFAULT_DEFINITIONS);
+        let commented = format!("// A new comment.\n{}", DEFAULT_DEFINITIONS);
+        // Same stone, with its fields reordered onto one line.
+        let rewritten = commented.replace(
+            "        render: Solid,\n        tiles: Same((0, 1)),",
+            "tiles: Same((0, 1)), render: Solid,",
+        );
+        assert_ne!(rewritten, commented);
+        assert_eq!(fingerprint(&rewritten), original);
+
+        let softer_stone = DEFAULT_DEFINITIONS.replace("hardness: 1.5,", "hardness: 1.4,");
+        assert_ne!(fingerprint(&softer_stone), original);
+    }
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+/// One of the separate worlds a server hosts, each with its own terrain and saved
+/// chunks. Sent by variant index, so new dimensions must be appended.
+#[derive(
+    Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default, Serialize, Deserialize,
+)]
+pub enum Dimension {
+    #[default]
+    Overworld,
+    Nether,
+}
+
+impl Dimension {
+    pub const ALL: &'static [Dimension] = &[Dimension::Overworld, Dimension::Nether];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Dimension::Overworld => "Overworld",
+            Dimension::Nether => "Nether",
+        }
+    }
+}
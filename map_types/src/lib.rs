@@ -1,492 +1,228 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::convert::TryInto;
 
+mod biome;
+pub mod block;
+mod dimension;
+pub mod palette;
+mod section;
+
+pub use biome::Biome;
+pub use block::{Block, BlockProperties, FluidLevel, RenderClass, Tiles, Transparency};
+pub use dimension::Dimension;
+use palette::PalettedSection;
+pub use section::Section;
+
 pub const CHUNK_SIZE: usize = 16;
 pub const MAP_HEIGHT: usize = 256;
+/// Height of the vertical slices a chunk is split into for storage.
+pub const SECTION_HEIGHT: usize = 16;
+pub const SECTION_COUNT: usize = MAP_HEIGHT / SECTION_HEIGHT;
+pub const SECTION_VOLUME: usize = CHUNK_SIZE * CHUNK_SIZE * SECTION_HEIGHT;
+/// A block id from `blocks.ron`. Ids are stable, so this is also what the
+/// network protocol and the save files store.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Debug)]
+pub struct Cube(pub u16);
 
-#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
-pub enum Cube {
-    Empty,
-    Solid(Solid),
-    Translucent(Translucent),
-    FilteredSolid(FilteredSolid),
-    Plantlike(Plantlike),
-    Harvestable(Harvestable),
-    Custom(Custom),
-}
-
-macro_rules! define_solid {
-    ($($variant:ident($($vals:tt),*)),* $(,)?) => {
-        #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
-        pub enum Solid {
-            $($variant),*
-        }
-
-        impl Solid {
-            pub fn tex_coord_px(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_solid!(@tex_coord_px $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn tex_coord_nx(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_solid!(@tex_coord_nx $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn tex_coord_py(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_solid!(@tex_coord_py $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn tex_coord_ny(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_solid!(@tex_coord_ny $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn tex_coord_pz(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_solid!(@tex_coord_pz $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn tex_coord_nz(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_solid!(@tex_coord_nz $($vals),*)
-                        }
-                    ),*
-                }
-            }
-        }
-    };
-
-    (@tex_coord_px ($y:expr, $x:expr)) => {
-        define_solid!(@tex_coord_px ($y, $x), ($y, $x), ($y, $x))
-    };
-    (@tex_coord_px ($side_y:expr, $side_x:expr), ($top_y:expr, $top_x:expr), ($bottom_y:expr, $bottom_x:expr)) => {
-        [[($side_x + 1) as f32, ($side_y + 1) as f32],
-         [$side_x as f32, ($side_y + 1) as f32],
-         [$side_x as f32, $side_y as f32],
-         [($side_x + 1) as f32, $side_y as f32]]
-    };
-    (@tex_coord_nx ($y:expr, $x:expr)) => {
-        define_solid!(@tex_coord_nx ($y, $x), ($y, $x), ($y, $x))
-    };
-    (@tex_coord_nx ($side_y:expr, $side_x:expr), ($top_y:expr, $top_x:expr), ($bottom_y:expr, $bottom_x:expr)) => {
-        [[($side_x + 1) as f32, $side_y as f32],
-         [$side_x as f32, $side_y as f32],
-         [$side_x as f32, ($side_y + 1) as f32],
-         [($side_x + 1) as f32, ($side_y + 1) as f32]]
-    };
-    (@tex_coord_py ($y:expr, $x:expr)) => {
-        define_solid!(@tex_coord_py ($y, $x), ($y, $x), ($y, $x))
-    };
-    (@tex_coord_py ($side_y:expr, $side_x:expr), ($top_y:expr, $top_x:expr), ($bottom_y:expr, $bottom_x:expr)) => {
-        [[$side_x as f32, ($side_y + 1) as f32],
-         [($side_x + 1) as f32, ($side_y + 1) as f32],
-         [($side_x + 1) as f32, $side_y as f32],
-         [$side_x as f32, $side_y as f32]]
-    };
-    (@tex_coord_ny ($y:expr, $x:expr)) => {
-        define_solid!(@tex_coord_ny ($y, $x), ($y, $x), ($y, $x))
-    };
-    (@tex_coord_ny ($side_y:expr, $side_x:expr), ($top_y:expr, $top_x:expr), ($bottom_y:expr, $bottom_x:expr)) => {
-        [[$side_x as f32, $side_y as f32],
-         [($side_x + 1) as f32, $side_y as f32],
-         [($side_x + 1) as f32, ($side_y + 1) as f32],
-         [$side_x as f32, ($side_y + 1) as f32]]
-    };
-    (@tex_coord_pz ($y:expr, $x:expr)) => {
-        define_solid!(@tex_coord_pz ($y, $x), ($y, $x), ($y, $x))
-    };
-    (@tex_coord_pz ($side_y:expr, $side_x:expr), ($top_y:expr, $top_x:expr), ($bottom_y:expr, $bottom_x:expr)) => {
-        [[($top_x + 1) as f32, ($top_y + 1) as f32],
-         [$top_x as f32, ($top_y + 1) as f32],
-         [$top_x as f32, $top_y as f32],
-         [($top_x + 1) as f32, $top_y as f32]]
-    };
-    (@tex_coord_nz ($y:expr, $x:expr)) => {
-        define_solid!(@tex_coord_nz ($y, $x), ($y, $x), ($y, $x))
-    };
-    (@tex_coord_nz ($side_y:expr, $side_x:expr), ($top_y:expr, $top_x:expr), ($bottom_y:expr, $bottom_x:expr)) => {
-        [[($bottom_x + 1) as f32, ($bottom_y + 1) as f32],
-         [$bottom_x as f32, ($bottom_y + 1) as f32],
-         [$bottom_x as f32, $bottom_y as f32],
-         [($bottom_x + 1) as f32, $bottom_y as f32]]
-    };
-}
-
-define_solid! {
-    Bedrock((1, 1)),
-    Dirt((0, 2)),
-    Stone((0, 1)),
-    PlankOak((0, 4)),
-    PlankBirch((13, 6)),
-    PlankJungle((12, 7)),
-    PlankSpruce((12, 6)),
-    SmoothStone((0, 6)),
-    SmoothStoneSlabs((0, 5), (0, 6), (0, 6)),
-    Bricks((0, 7)),
-    TNT((0, 8), (0, 9), (0, 10)),
-    Cobblestone((1, 0)),
-    Sand((1, 2)),
-    Gravel((1, 3)),
-    OakLog((1, 4), (1, 5), (1, 5)),
-    BlockOfIron((1, 6)),
-    BlockOfGold((1, 7)),
-    BlockOfDiamond((1, 8)),
-    GoldOre((2, 0)),
-    IronOre((2, 1)),
-    CoalOre((2, 2)),
-    Bookshelf((2, 3), (0, 4), (0, 4)),
-    MossyCobblestone((2, 4)),
-    Obsidian((2, 5)),
-    Sponge((3, 0)),
-    DiamondOre((3, 2)),
-    RedstoneOre((3, 3)),
-    StoneBricks((3, 5)),
-    WoolWhite((4, 0)),
-    SnowBlock((4, 2)),
-    SnowyGrassBlock((4, 4), (4, 2), (0, 2)),
-    Clay((4, 8)),
-    Jukebox((4, 10), (4, 11), (4, 10)),
-    Mycelium((4, 13), (4, 14), (0, 2)),
+impl Cube {
+    pub const EMPTY: Cube = Cube(0);
 }
 
-macro_rules! define_translucent {
-    ($($variant:ident($($vals:tt),*)),* $(,)?) => {
-        #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
-        pub enum Translucent {
-            $($variant),*
-        }
+/// Blocks the code refers to directly. Their ids are checked against
+/// `blocks.ron` when the definitions are loaded.
+pub mod blocks {
+    use crate::{Cube, FluidLevel};
 
-        impl Translucent {
-            pub fn tex_coord(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_translucent!(@tex_coord $($vals),*)
-                        }
-                    ),*
-                }
-            }
-        }
-    };
+    macro_rules! known_blocks {
+        ($($constant:ident = $id:literal $name:literal),* $(,)?) => {
+            $(pub const $constant: Cube = Cube($id);)*
 
-    (@tex_coord $y:expr, $x:expr) => {
-        [[($x + 1) as f32, ($y + 1) as f32],
-         [$x as f32, ($y + 1) as f32],
-         [$x as f32, $y as f32],
-         [($x + 1) as f32, $y as f32]]
-    };
-}
+            pub(crate) const KNOWN: &[(Cube, &str)] = &[$(($constant, $name)),*];
+        };
+    }
 
-define_translucent! {
-    Glass(3, 1),
-    OakLeaves(3, 4),
-    MonsterSpawner(4, 1),
-    Ice(4, 3),
-}
+    known_blocks! {
+        AIR = 0 "air",
+        BEDROCK = 1 "bedrock",
+        DIRT = 2 "dirt",
+        STONE = 3 "stone",
+        OAK_PLANKS = 4 "oak_planks",
+        COBBLESTONE = 12 "cobblestone",
+        SAND = 13 "sand",
+        GRAVEL = 14 "gravel",
+        OAK_LOG = 15 "oak_log",
+        GOLD_ORE = 19 "gold_ore",
+        IRON_ORE = 20 "iron_ore",
+        COAL_ORE = 21 "coal_ore",
+        BOOKSHELF = 22 "bookshelf",
+        OBSIDIAN = 24 "obsidian",
+        DIAMOND_ORE = 26 "diamond_ore",
+        REDSTONE_ORE = 27 "redstone_ore",
+        SNOWY_GRASS_BLOCK = 31 "snowy_grass_block",
+        MYCELIUM = 34 "mycelium",
+        GLASS = 35 "glass",
+        OAK_LEAVES = 36 "oak_leaves",
+        ICE = 38 "ice",
+        GRASS_BLOCK = 39 "grass_block",
+        GRASS = 40 "grass",
+        RED_FLOWER = 41 "red_flower",
+        YELLOW_FLOWER = 42 "yellow_flower",
+        RED_MUSHROOM = 43 "red_mushroom",
+        BROWN_MUSHROOM = 44 "brown_mushroom",
+        DEAD_BUSH = 50 "dead_bush",
+        WHEAT_1 = 52 "wheat_1",
+        WHEAT_2 = 53 "wheat_2",
+        WHEAT_3 = 54 "wheat_3",
+        WHEAT_4 = 55 "wheat_4",
+        WHEAT_5 = 56 "wheat_5",
+        WHEAT_6 = 57 "wheat_6",
+        WHEAT_7 = 58 "wheat_7",
+        WHEAT_8 = 59 "wheat_8",
+        NETHER_WART_1 = 60 "nether_wart_1",
+        NETHER_WART_2 = 61 "nether_wart_2",
+        NETHER_WART_3 = 62 "nether_wart_3",
+        CACTUS = 63 "cactus",
+        WATER = 64 "water",
+        FLOWING_WATER_1 = 65 "flowing_water_1",
+        FLOWING_WATER_2 = 66 "flowing_water_2",
+        FLOWING_WATER_3 = 67 "flowing_water_3",
+        FLOWING_WATER_4 = 68 "flowing_water_4",
+        FLOWING_WATER_5 = 69 "flowing_water_5",
+        FLOWING_WATER_6 = 70 "flowing_water_6",
+        FLOWING_WATER_7 = 71 "flowing_water_7",
+        BIRCH_LOG = 72 "birch_log",
+        SPRUCE_LOG = 73 "spruce_log",
+        JUNGLE_LOG = 74 "jungle_log",
+        BIRCH_LEAVES = 75 "birch_leaves",
+        SPRUCE_LEAVES = 76 "spruce_leaves",
+        JUNGLE_LEAVES = 77 "jungle_leaves",
+        NETHERRACK = 78 "netherrack",
+        SOUL_SAND = 79 "soul_sand",
+        GLOWSTONE = 80 "glowstone",
+        LAVA = 81 "lava",
+        NETHER_PORTAL = 82 "nether_portal",
+    }
 
-macro_rules! define_filtered_solid {
-    ($($variant:ident($($vals:tt),*)),* $(,)?) => {
-        #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
-        pub enum FilteredSolid {
-            $($variant),*
+    const FLOWING_WATER: [Cube; FluidLevel::MAX_FLOW as usize] = [
+        FLOWING_WATER_1,
+        FLOWING_WATER_2,
+        FLOWING_WATER_3,
+        FLOWING_WATER_4,
+        FLOWING_WATER_5,
+        FLOWING_WATER_6,
+        FLOWING_WATER_7,
+    ];
+
+    /// The water cube at the given level.
+    pub fn water(level: FluidLevel) -> Cube {
+        match level {
+            FluidLevel::Source => WATER,
+            FluidLevel::Flowing(level) => FLOWING_WATER[level as usize - 1],
         }
-
-        impl FilteredSolid {
-            pub fn extras_px(&self) -> ([[f32; 2]; 4], [[f32; 2]; 4]) {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_filtered_solid!(@extras_px $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn extras_nx(&self) -> ([[f32; 2]; 4], [[f32; 2]; 4]) {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_filtered_solid!(@extras_nx $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn extras_py(&self) -> ([[f32; 2]; 4], [[f32; 2]; 4]) {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_filtered_solid!(@extras_py $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn extras_ny(&self) -> ([[f32; 2]; 4], [[f32; 2]; 4]) {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_filtered_solid!(@extras_ny $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn extras_pz(&self) -> ([[f32; 2]; 4], [[f32; 2]; 4]) {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_filtered_solid!(@extras_pz $($vals),*)
-                        }
-                    ),*
-                }
-            }
-            pub fn extras_nz(&self) -> ([[f32; 2]; 4], [[f32; 2]; 4]) {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_filtered_solid!(@extras_nz $($vals),*)
-                        }
-                    ),*
-                }
-            }
-        }
-    };
-
-    (@extras_px ($original_y:expr, $original_x:expr), ($filter_y:expr, $filter_x:expr)) => {
-        define_filtered_solid!(@extras_px ($original_y, $original_x), ($original_y, $original_x), ($original_y, $original_x), ($filter_y, $filter_x), ($filter_y, $filter_x), ($filter_y, $filter_x))
-    };
-    (@extras_px ($original_side_y:expr, $original_side_x:expr), ($original_top_y:expr, $original_top_x:expr), ($original_bottom_y:expr, $original_bottom_x:expr), ($filter_side_y:expr, $filter_side_x:expr), ($filter_top_y:expr, $filter_top_x:expr), ($filter_bottom_y:expr, $filter_bottom_x:expr)) => {
-        ([[($original_side_x + 1) as f32, ($original_side_y + 1) as f32],
-          [$original_side_x as f32, ($original_side_y + 1) as f32],
-          [$original_side_x as f32, $original_side_y as f32],
-          [($original_side_x + 1) as f32, $original_side_y as f32]],
-         [[($filter_side_x + 1) as f32, ($filter_side_y + 1) as f32],
-          [$filter_side_x as f32, ($filter_side_y + 1) as f32],
-          [$filter_side_x as f32, $filter_side_y as f32],
-          [($filter_side_x + 1) as f32, $filter_side_y as f32]])
-    };
-    (@extras_nx ($original_y:expr, $original_x:expr), ($filter_y:expr, $filter_x:expr)) => {
-        define_filtered_solid!(@extras_nx ($original_y, $original_x), ($original_y, $original_x), ($original_y, $original_x), ($filter_y, $filter_x), ($filter_y, $filter_x), ($filter_y, $filter_x))
-    };
-    (@extras_nx ($original_side_y:expr, $original_side_x:expr), ($original_top_y:expr, $original_top_x:expr), ($original_bottom_y:expr, $original_bottom_x:expr), ($filter_side_y:expr, $filter_side_x:expr), ($filter_top_y:expr, $filter_top_x:expr), ($filter_bottom_y:expr, $filter_bottom_x:expr)) => {
-        ([[($original_side_x + 1) as f32, $original_side_y as f32],
-          [$original_side_x as f32, $original_side_y as f32],
-          [$original_side_x as f32, ($original_side_y + 1) as f32],
-          [($original_side_x + 1) as f32, ($original_side_y + 1) as f32]],
-         [[($filter_side_x + 1) as f32, $filter_side_y as f32],
-          [$filter_side_x as f32, $filter_side_y as f32],
-          [$filter_side_x as f32, ($filter_side_y + 1) as f32],
-          [($filter_side_x + 1) as f32, ($filter_side_y + 1) as f32]])
-    };
-    (@extras_py ($original_y:expr, $original_x:expr), ($filter_y:expr, $filter_x:expr)) => {
-        define_filtered_solid!(@extras_py ($original_y, $original_x), ($original_y, $original_x), ($original_y, $original_x), ($filter_y, $filter_x), ($filter_y, $filter_x), ($filter_y, $filter_x))
-    };
-    (@extras_py ($original_side_y:expr, $original_side_x:expr), ($original_top_y:expr, $original_top_x:expr), ($original_bottom_y:expr, $original_bottom_x:expr), ($filter_side_y:expr, $filter_side_x:expr), ($filter_top_y:expr, $filter_top_x:expr), ($filter_bottom_y:expr, $filter_bottom_x:expr)) => {
-        ([[$original_side_x as f32, ($original_side_y + 1) as f32],
-          [($original_side_x + 1) as f32, ($original_side_y + 1) as f32],
-          [($original_side_x + 1) as f32, $original_side_y as f32],
-          [$original_side_x as f32, $original_side_y as f32]],
-         [[$filter_side_x as f32, ($filter_side_y + 1) as f32],
-          [($filter_side_x + 1) as f32, ($filter_side_y + 1) as f32],
-          [($filter_side_x + 1) as f32, $filter_side_y as f32],
-          [$filter_side_x as f32, $filter_side_y as f32]])
-    };
-    (@extras_ny ($original_y:expr, $original_x:expr), ($filter_y:expr, $filter_x:expr)) => {
-        define_filtered_solid!(@extras_ny ($original_y, $original_x), ($original_y, $original_x), ($original_y, $original_x), ($filter_y, $filter_x), ($filter_y, $filter_x), ($filter_y, $filter_x))
-    };
-    (@extras_ny ($original_side_y:expr, $original_side_x:expr), ($original_top_y:expr, $original_top_x:expr), ($original_bottom_y:expr, $original_bottom_x:expr), ($filter_side_y:expr, $filter_side_x:expr), ($filter_top_y:expr, $filter_top_x:expr), ($filter_bottom_y:expr, $filter_bottom_x:expr)) => {
-        ([[$original_side_x as f32, $original_side_y as f32],
-          [($original_side_x + 1) as f32, $original_side_y as f32],
-          [($original_side_x + 1) as f32, ($original_side_y + 1) as f32],
-          [$original_side_x as f32, ($original_side_y + 1) as f32]],
-         [[$filter_side_x as f32, $filter_side_y as f32],
-          [($filter_side_x + 1) as f32, $filter_side_y as f32],
-          [($filter_side_x + 1) as f32, ($filter_side_y + 1) as f32],
-          [$filter_side_x as f32, ($filter_side_y + 1) as f32]])
-    };
-    (@extras_pz ($original_y:expr, $original_x:expr), ($filter_y:expr, $filter_x:expr)) => {
-        define_filtered_solid!(@extras_pz ($original_y, $original_x), ($original_y, $original_x), ($original_y, $original_x), ($filter_y, $filter_x), ($filter_y, $filter_x), ($filter_y, $filter_x))
-    };
-    (@extras_pz ($original_side_y:expr, $original_side_x:expr), ($original_top_y:expr, $original_top_x:expr), ($original_bottom_y:expr, $original_bottom_x:expr), ($filter_side_y:expr, $filter_side_x:expr), ($filter_top_y:expr, $filter_top_x:expr), ($filter_bottom_y:expr, $filter_bottom_x:expr)) => {
-        ([[($original_top_x + 1) as f32, ($original_top_y + 1) as f32],
-          [$original_top_x as f32, ($original_top_y + 1) as f32],
-          [$original_top_x as f32, $original_top_y as f32],
-          [($original_top_x + 1) as f32, $original_top_y as f32]],
-         [[($filter_top_x + 1) as f32, ($filter_top_y + 1) as f32],
-          [$filter_top_x as f32, ($filter_top_y + 1) as f32],
-          [$filter_top_x as f32, $filter_top_y as f32],
-          [($filter_top_x + 1) as f32, $filter_top_y as f32]])
-    };
-    (@extras_nz ($original_y:expr, $original_x:expr), ($filter_y:expr, $filter_x:expr)) => {
-        define_filtered_solid!(@extras_nz ($original_y, $original_x), ($original_y, $original_x), ($original_y, $original_x), ($filter_y, $filter_x), ($filter_y, $filter_x), ($filter_y, $filter_x))
-    };
-    (@extras_nz ($original_side_y:expr, $original_side_x:expr), ($original_top_y:expr, $original_top_x:expr), ($original_bottom_y:expr, $original_bottom_x:expr), ($filter_side_y:expr, $filter_side_x:expr), ($filter_top_y:expr, $filter_top_x:expr), ($filter_bottom_y:expr, $filter_bottom_x:expr)) => {
-        ([[($original_bottom_x + 1) as f32, ($original_bottom_y + 1) as f32],
-          [$original_bottom_x as f32, ($original_bottom_y + 1) as f32],
-          [$original_bottom_x as f32, $original_bottom_y as f32],
-          [($original_bottom_x + 1) as f32, $original_bottom_y as f32]],
-         [[($filter_bottom_x + 1) as f32, ($filter_bottom_y + 1) as f32],
-          [$filter_bottom_x as f32, ($filter_bottom_y + 1) as f32],
-          [$filter_bottom_x as f32, $filter_bottom_y as f32],
-          [($filter_bottom_x + 1) as f32, $filter_bottom_y as f32]])
-    };
+    }
 }
 
-define_filtered_solid! {
-    GrassBlock((0, 3), (0, 0), (0, 2), (2, 6), (11, 4), (11, 5)),
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chunk {
+    sections: [Section; SECTION_COUNT],
+    /// Indexed by `y * CHUNK_SIZE + x`, like `biome_colors`.
+    pub biomes: [Biome; CHUNK_SIZE * CHUNK_SIZE],
+    /// Grass tint per column, blended across biome borders.
+    pub biome_colors: [[f32; 4]; CHUNK_SIZE * CHUNK_SIZE],
 }
 
-macro_rules! define_plantlike {
-    ($($variant:ident($($vals:tt),*)),* $(,)?) => {
-        #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
-        pub enum Plantlike {
-            $($variant),*
-        }
-
-        impl Plantlike {
-            pub fn tex_coord(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_plantlike!(@tex_coord $($vals),*)
-                        }
-                    ),*
-                }
-            }
+impl Default for Chunk {
+    fn default() -> Self {
+        Chunk {
+            sections: Default::default(),
+            biomes: [Biome::default(); CHUNK_SIZE * CHUNK_SIZE],
+            biome_colors: [[0.0; 4]; CHUNK_SIZE * CHUNK_SIZE],
         }
-    };
-
-    (@tex_coord $y:expr, $x:expr) => {
-        [[($x + 1) as f32, ($y + 1) as f32],
-         [$x as f32, ($y + 1) as f32],
-         [$x as f32, $y as f32],
-         [($x + 1) as f32, $y as f32]]
-    };
-}
-
-define_plantlike! {
-    Grass(2, 7),
-    FlowerRed(0, 12),
-    FlowerYellow(0, 13),
-    MushroomRed(1, 12),
-    MushroomBrown(1, 13),
-    TreeSamplingOak(0, 15),
-    TreeSamplingBirch(4, 15),
-    TreeSamplingJungle(1, 14),
-    TreeSamplingSpruce(3, 15),
-    TreeSamplingLikeIDK(3, 8),
-    DeadBush(3, 7),
-    Cobweb(0, 11),
+    }
 }
 
-macro_rules! define_harvestable {
-    ($($variant:ident($($vals:tt),*)),* $(,)?) => {
-        #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
-        pub enum Harvestable {
-            $($variant),*
-        }
-
-        impl Harvestable {
-            pub fn tex_coord(&self) -> [[f32; 2]; 4] {
-                match self {
-                    $(
-                        Self::$variant => {
-                            define_harvestable!(@tex_coord $($vals),*)
-                        }
-                    ),*
-                }
-            }
-        }
-    };
+impl Chunk {
+    /// An all-empty chunk.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    (@tex_coord $y:expr, $x:expr) => {
-        [[($x + 1) as f32, ($y + 1) as f32],
-         [$x as f32, ($y + 1) as f32],
-         [$x as f32, $y as f32],
-         [($x + 1) as f32, $y as f32]]
-    };
-}
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Cube {
+        self.sections[z / SECTION_HEIGHT].get(x, y, z % SECTION_HEIGHT)
+    }
 
-define_harvestable! {
-    Wheat1(5, 8),
-    Wheat2(5, 9),
-    Wheat3(5, 10),
-    Wheat4(5, 11),
-    Wheat5(5, 12),
-    Wheat6(5, 13),
-    Wheat7(5, 14),
-    Wheat8(5, 15),
-    NetherWart1(14, 2),
-    NetherWart2(14, 3),
-    NetherWart3(14, 4),
-}
+    pub fn set(&mut self, x: usize, y: usize, z: usize, cube: Cube) {
+        self.sections[z / SECTION_HEIGHT].set(x, y, z % SECTION_HEIGHT, cube);
+    }
 
-#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Debug)]
-pub enum Custom {
-    Cactus,
-}
+    /// Sections from the bottom (`z = 0`) up.
+    pub fn sections(&self) -> &[Section; SECTION_COUNT] {
+        &self.sections
+    }
 
-#[derive(Clone, Debug)]
-pub struct Chunk {
-    pub cubes: [Cube; MAP_HEIGHT * CHUNK_SIZE * CHUNK_SIZE],
-    pub biome_colors: [[f32; 4]; CHUNK_SIZE * CHUNK_SIZE],
+    pub fn biome(&self, x: usize, y: usize) -> Biome {
+        self.biomes[y * CHUNK_SIZE + x]
+    }
 }
 
 impl Serialize for Chunk {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let cubes_vec: Vec<Cube> = self.cubes.to_vec();
+        // Empty sections are sent as `None` without building a palette.
+        let sections: Vec<Option<PalettedSection>> = self
+            .sections
+            .iter()
+            .map(|section| section.cubes().map(|cubes| PalettedSection::encode(cubes)))
+            .collect();
+        let biomes_vec: Vec<Biome> = self.biomes.to_vec();
         let biome_colors_vec: Vec<[f32; 4]> = self.biome_colors.to_vec();
-        (cubes_vec, biome_colors_vec).serialize(serializer)
+        (sections, biomes_vec, biome_colors_vec).serialize(serializer)
     }
 }
 
 impl<'de> Deserialize<'de> for Chunk {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let (cubes_vec, biome_colors_vec): (Vec<Cube>, Vec<[f32; 4]>) =
-            Deserialize::deserialize(deserializer)?;
-        let cubes: [Cube; MAP_HEIGHT * CHUNK_SIZE * CHUNK_SIZE] = cubes_vec
+        let (paletted_sections, biomes_vec, biome_colors_vec): (
+            Vec<Option<PalettedSection>>,
+            Vec<Biome>,
+            Vec<[f32; 4]>,
+        ) = Deserialize::deserialize(deserializer)?;
+        if paletted_sections.len() != SECTION_COUNT {
+            return Err(serde::de::Error::custom("Invalid section count"));
+        }
+        let mut sections: [Section; SECTION_COUNT] = Default::default();
+        for (section, paletted) in sections.iter_mut().zip(paletted_sections) {
+            if let Some(paletted) = paletted {
+                let mut cubes = Box::new([Cube::EMPTY; SECTION_VOLUME]);
+                paletted
+                    .decode_into(&mut cubes[..])
+                    .map_err(serde::de::Error::custom)?;
+                *section = Section::from_cubes(cubes);
+            }
+        }
+        let biomes: [Biome; CHUNK_SIZE * CHUNK_SIZE] = biomes_vec
             .try_into()
-            .map_err(|_| serde::de::Error::custom("Invalid cube array length"))?;
+            .map_err(|_| serde::de::Error::custom("Invalid biomes array length"))?;
         let biome_colors: [[f32; 4]; CHUNK_SIZE * CHUNK_SIZE] = biome_colors_vec
             .try_into()
             .map_err(|_| serde::de::Error::custom("Invalid biome_colors array length"))?;
         Ok(Chunk {
-            cubes,
+            sections,
+            biomes,
             biome_colors,
         })
     }
 }
 
 impl Cube {
+    /// Hides the faces of neighbouring cubes; see `Transparency::Opaque`.
     pub fn is_solid(&self) -> bool {
-        matches!(self, Cube::Solid(_) | Cube::FilteredSolid(_))
+        self.properties().transparency == Transparency::Opaque
     }
 
     pub fn is_translucent_or_solid(&self) -> bool {
         matches!(
-            self,
-            Cube::Translucent(_) | Cube::Solid(_) | Cube::FilteredSolid(_)
+            self.properties().transparency,
+            Transparency::Opaque | Transparency::Translucent
         )
     }
 }
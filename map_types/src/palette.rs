@@ -0,0 +1,224 @@
+//! Compact storage for one vertical section of a chunk.
+//!
+//! Each distinct cube in the section is listed once in a palette and every
+//! position stores only its palette index, packed with as few bits as the
+//! palette size allows. Indices never straddle two words, so a word holds
+//! `64 / bits` of them. A section made of a single cube stores no indices at all.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::{Cube, SECTION_VOLUME};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PaletteError {
+    EmptyPalette,
+    BitsMismatch { bits: u8, palette_len: usize },
+    DataLength { expected: usize, actual: usize },
+    IndexOutOfRange { index: usize, palette_len: usize },
+    UnknownBlock(u16),
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::EmptyPalette => write!(f, "section palette is empty"),
+            PaletteError::BitsMismatch { bits, palette_len } => write!(
+                f,
+                "{} bits per cube do not fit a palette of {}",
+                bits, palette_len
+            ),
+            PaletteError::DataLength { expected, actual } => {
+                write!(f, "section holds {} words, expected {}", actual, expected)
+            }
+            PaletteError::IndexOutOfRange { index, palette_len } => write!(
+                f,
+                "palette index {} out of range for a palette of {}",
+                index, palette_len
+            ),
+            PaletteError::UnknownBlock(id) => write!(f, "block id {} is not defined", id),
+        }
+    }
+}
+
+impl std::error::Error for PaletteError {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PalettedSection {
+    palette: Vec<Cube>,
+    bits: u8,
+    data: Vec<u64>,
+}
+
+fn bits_for(palette_len: usize) -> u8 {
+    if palette_len <= 1 {
+        0
+    } else {
+        (usize::BITS - (palette_len - 1).leading_zeros()) as u8
+    }
+}
+
+fn word_count(bits: u8) -> usize {
+    if bits == 0 {
+        0
+    } else {
+        SECTION_VOLUME.div_ceil(64 / bits as usize)
+    }
+}
+
+impl PalettedSection {
+    /// Packs `SECTION_VOLUME` cubes laid out like `Section::index`.
+    pub fn encode(cubes: &[Cube]) -> Self {
+        assert_eq!(cubes.len(), SECTION_VOLUME);
+
+        let mut palette: Vec<Cube> = Vec::new();
+        let mut indices = Vec::with_capacity(SECTION_VOLUME);
+        let mut last = None;
+        for cube in cubes {
+            let index = match last {
+                Some((last_cube, last_index)) if last_cube == *cube => last_index,
+                _ => match palette.iter().position(|p| p == cube) {
+                    Some(index) => index,
+                    None => {
+                        palette.push(*cube);
+                        palette.len() - 1
+                    }
+                },
+            };
+            last = Some((*cube, index));
+            indices.push(index as u64);
+        }
+
+        let bits = bits_for(palette.len());
+        let mut data = vec![0u64; word_count(bits)];
+        if bits > 0 {
+            let per_word = 64 / bits as usize;
+            for (i, index) in indices.into_iter().enumerate() {
+                data[i / per_word] |= index << ((i % per_word) * bits as usize);
+            }
+        }
+
+        PalettedSection {
+            palette,
+            bits,
+            data,
+        }
+    }
+
+    /// Unpacks the section into `out`, which must hold `SECTION_VOLUME` cubes.
+    pub fn decode_into(&self, out: &mut [Cube]) -> Result<(), PaletteError> {
+        assert_eq!(out.len(), SECTION_VOLUME);
+        self.validate()?;
+
+        if self.bits == 0 {
+            out.fill(self.palette[0]);
+            return Ok(());
+        }
+        let bits = self.bits as usize;
+        let per_word = 64 / bits;
+        let mask = (1u64 << bits) - 1;
+        for (i, cube) in out.iter_mut().enumerate() {
+            let index = ((self.data[i / per_word] >> ((i % per_word) * bits)) & mask) as usize;
+            *cube = *self
+                .palette
+                .get(index)
+                .ok_or(PaletteError::IndexOutOfRange {
+                    index,
+                    palette_len: self.palette.len(),
+                })?;
+        }
+        Ok(())
+    }
+
+    pub fn palette(&self) -> &[Cube] {
+        &self.palette
+    }
+
+    fn validate(&self) -> Result<(), PaletteError> {
+        if self.palette.is_empty() {
+            return Err(PaletteError::EmptyPalette);
+        }
+        if let Some(cube) = self.palette.iter().find(|cube| !cube.is_defined()) {
+            return Err(PaletteError::UnknownBlock(cube.0));
+        }
+        if self.bits != bits_for(self.palette.len()) {
+            return Err(PaletteError::BitsMismatch {
+                bits: self.bits,
+                palette_len: self.palette.len(),
+            });
+        }
+        let expected = word_count(self.bits);
+        if self.data.len() != expected {
+            return Err(PaletteError::DataLength {
+                expected,
+                actual: self.data.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks;
+
+    fn varied_section(kinds: usize) -> Vec<Cube> {
+        let choices = [
+            Cube::EMPTY,
+            blocks::STONE,
+            blocks::DIRT,
+            blocks::BEDROCK,
+            blocks::GLASS,
+        ];
+        (0..SECTION_VOLUME)
+            .map(|i| choices[(i * 7 + i / 13) % kinds])
+            .collect()
+    }
+
+    fn roundtrip(cubes: &[Cube]) -> PalettedSection {
+        let section = PalettedSection::encode(cubes);
+        let mut decoded = vec![Cube::EMPTY; SECTION_VOLUME];
+        section.decode_into(&mut decoded).unwrap();
+        assert_eq!(decoded, cubes);
+        section
+    }
+
+    #[test]
+    fn uniform_section_stores_no_indices() {
+        let section = roundtrip(&vec![blocks::STONE; SECTION_VOLUME]);
+        assert_eq!(section.bits, 0);
+        assert!(section.data.is_empty());
+    }
+
+    #[test]
+    fn mixed_sections_roundtrip() {
+        for kinds in 2..=5 {
+            let section = roundtrip(&varied_section(kinds));
+            assert_eq!(section.palette().len(), kinds);
+            assert_eq!(section.bits, bits_for(kinds));
+        }
+    }
+
+    #[test]
+    fn out_of_range_index_is_rejected() {
+        let mut section = PalettedSection::encode(&varied_section(3));
+        section.data[0] |= 3;
+        let mut decoded = vec![Cube::EMPTY; SECTION_VOLUME];
+        assert!(matches!(
+            section.decode_into(&mut decoded),
+            Err(PaletteError::IndexOutOfRange { index: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_block_is_rejected() {
+        let mut section = PalettedSection::encode(&varied_section(2));
+        section.palette[1] = Cube(u16::MAX);
+        let mut decoded = vec![Cube::EMPTY; SECTION_VOLUME];
+        assert_eq!(
+            section.decode_into(&mut decoded),
+            Err(PaletteError::UnknownBlock(u16::MAX))
+        );
+    }
+}
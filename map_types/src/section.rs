@@ -0,0 +1,100 @@
+use crate::{Cube, CHUNK_SIZE, SECTION_VOLUME};
+
+/// A 16x16x`SECTION_HEIGHT` slice of a chunk.
+///
+/// Keeps count of its non-empty and solid cubes so callers can skip sections
+/// that are all air or completely filled without looking at every cube.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Section {
+    /// `None` while every cube is `Cube::EMPTY`.
+    cubes: Option<Box<[Cube; SECTION_VOLUME]>>,
+    non_empty: u16,
+    solid: u16,
+}
+
+impl Section {
+    pub fn from_cubes(cubes: Box<[Cube; SECTION_VOLUME]>) -> Self {
+        let non_empty = cubes.iter().filter(|cube| **cube != Cube::EMPTY).count() as u16;
+        let solid = cubes.iter().filter(|cube| cube.is_solid()).count() as u16;
+        Section {
+            cubes: (non_empty > 0).then_some(cubes),
+            non_empty,
+            solid,
+        }
+    }
+
+    /// `z` is relative to the bottom of the section.
+    pub fn index(x: usize, y: usize, z: usize) -> usize {
+        z * CHUNK_SIZE * CHUNK_SIZE + y * CHUNK_SIZE + x
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> Cube {
+        match &self.cubes {
+            Some(cubes) => cubes[Self::index(x, y, z)],
+            None => Cube::EMPTY,
+        }
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, cube: Cube) {
+        if self.cubes.is_none() && cube == Cube::EMPTY {
+            return;
+        }
+        let cubes = self
+            .cubes
+            .get_or_insert_with(|| Box::new([Cube::EMPTY; SECTION_VOLUME]));
+        let previous = std::mem::replace(&mut cubes[Self::index(x, y, z)], cube);
+
+        self.non_empty =
+            self.non_empty + (cube != Cube::EMPTY) as u16 - (previous != Cube::EMPTY) as u16;
+        self.solid = self.solid + cube.is_solid() as u16 - previous.is_solid() as u16;
+        if self.non_empty == 0 {
+            self.cubes = None;
+        }
+    }
+
+    /// The cubes laid out like `Section::index`, or `None` if the section is empty.
+    pub fn cubes(&self) -> Option<&[Cube; SECTION_VOLUME]> {
+        self.cubes.as_deref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.non_empty == 0
+    }
+
+    /// Every cube is solid, so nothing inside can be seen from a solid neighbour.
+    pub fn is_full_solid(&self) -> bool {
+        self.solid as usize == SECTION_VOLUME
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{blocks, SECTION_HEIGHT};
+
+    #[test]
+    fn counts_follow_edits() {
+        let mut section = Section::default();
+        assert!(section.is_empty());
+
+        section.set(1, 2, 3, blocks::GRASS);
+        assert!(!section.is_empty());
+        assert_eq!(section.get(1, 2, 3), blocks::GRASS);
+
+        section.set(1, 2, 3, Cube::EMPTY);
+        assert!(section.is_empty());
+        assert!(section.cubes().is_none());
+    }
+
+    #[test]
+    fn full_solid_is_detected() {
+        let stone = blocks::STONE;
+        let mut section = Section::from_cubes(Box::new([stone; SECTION_VOLUME]));
+        assert!(section.is_full_solid());
+
+        section.set(0, 0, SECTION_HEIGHT - 1, blocks::GRASS);
+        assert!(!section.is_full_solid());
+        section.set(0, 0, SECTION_HEIGHT - 1, blocks::DIRT);
+        assert!(section.is_full_solid());
+    }
+}
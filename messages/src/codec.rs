@@ -0,0 +1,241 @@
+use std::fmt;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Size of the little-endian `u32` length header in front of every frame.
+pub const HEADER_SIZE: usize = 4;
+
+/// Largest payload a peer may announce. Anything bigger is treated as garbage.
+pub const MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum FrameError {
+    TooLarge(usize),
+    Encode(bincode::Error),
+    Decode(bincode::Error),
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::TooLarge(size) => write!(
+                f,
+                "frame of {} bytes exceeds the {} byte limit",
+                size, MAX_FRAME_SIZE
+            ),
+            FrameError::Encode(e) => write!(f, "failed to encode frame: {}", e),
+            FrameError::Decode(e) => write!(f, "failed to decode frame: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// Serializes `message` and prepends its length.
+pub fn encode<T: Serialize>(message: &T) -> Result<Vec<u8>, FrameError> {
+    let size = bincode::serialized_size(message).map_err(FrameError::Encode)? as usize;
+    if size > MAX_FRAME_SIZE {
+        return Err(FrameError::TooLarge(size));
+    }
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + size);
+    bytes.extend_from_slice(&(size as u32).to_le_bytes());
+    bincode::serialize_into(&mut bytes, message).map_err(FrameError::Encode)?;
+    Ok(bytes)
+}
+
+/// Accumulates raw stream bytes and splits them back into frames.
+#[derive(Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffer to read into, e.g. with `AsyncReadExt::read_buf`.
+    pub fn buffer_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buffer
+    }
+
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete message, or `None` if more bytes are needed.
+    pub fn decode<T: DeserializeOwned>(&mut self) -> Result<Option<T>, FrameError> {
+        let Some(size) = self.complete_frame_size()? else {
+            return Ok(None);
+        };
+        let result = bincode::deserialize(&self.buffer[HEADER_SIZE..HEADER_SIZE + size]);
+        self.buffer.drain(..HEADER_SIZE + size);
+        result.map(Some).map_err(FrameError::Decode)
+    }
+
+    /// Decodes a prefix of the next complete frame without consuming it.
+    pub fn peek<T: DeserializeOwned>(&self) -> Result<Option<T>, FrameError> {
+        let Some(size) = self.complete_frame_size()? else {
+            return Ok(None);
+        };
+        bincode::deserialize(&self.buffer[HEADER_SIZE..HEADER_SIZE + size])
+            .map(Some)
+            .map_err(FrameError::Decode)
+    }
+
+    fn complete_frame_size(&self) -> Result<Option<usize>, FrameError> {
+        if self.buffer.len() < HEADER_SIZE {
+            return Ok(None);
+        }
+        let mut header = [0u8; HEADER_SIZE];
+        header.copy_from_slice(&self.buffer[..HEADER_SIZE]);
+        let size = u32::from_le_bytes(header) as usize;
+        if size > MAX_FRAME_SIZE {
+            return Err(FrameError::TooLarge(size));
+        }
+        if self.buffer.len() < HEADER_SIZE + size {
+            return Ok(None);
+        }
+        Ok(Some(size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DisconnectReason, PlayerPosition, ServerMessage};
+    use map_types::{blocks, Chunk, Cube, Dimension, CHUNK_SIZE, MAP_HEIGHT};
+
+    /// Terrain-like layers: bedrock floor, stone, dirt, then air.
+    fn layered_chunk() -> Chunk {
+        let mut chunk = Chunk::new();
+        for z in 0..64 {
+            for y in 0..CHUNK_SIZE {
+                for x in 0..CHUNK_SIZE {
+                    let cube = match z {
+                        0 => blocks::BEDROCK,
+                        1..=59 => blocks::STONE,
+                        _ if (x + y) % 5 != 0 => blocks::DIRT,
+                        _ => Cube::EMPTY,
+                    };
+                    chunk.set(x, y, z, cube);
+                }
+            }
+        }
+        chunk.biome_colors = [[0.5; 4]; CHUNK_SIZE * CHUNK_SIZE];
+        chunk
+    }
+
+    fn chunk_message() -> ServerMessage {
+        ServerMessage::Chunk {
+            dimension: Dimension::Overworld,
+            x: 3,
+            y: -7,
+            chunk: Box::new(layered_chunk()),
+        }
+    }
+
+    fn move_message(id: u32) -> ServerMessage {
+        ServerMessage::PlayerMove {
+            moved_player_id: id,
+            position: PlayerPosition::NotInWorld,
+        }
+    }
+
+    #[test]
+    fn large_message_survives_byte_by_byte_delivery() {
+        let message = ServerMessage::Disconnect {
+            reason: DisconnectReason::InvalidData {
+                detail: "x".repeat(100_000),
+            },
+        };
+        let bytes = encode(&message).unwrap();
+        assert!(bytes.len() > 65536);
+
+        let mut decoder = FrameDecoder::new();
+        let mut decoded = None;
+        for (i, byte) in bytes.iter().enumerate() {
+            decoder.extend(&[*byte]);
+            let result = decoder.decode::<ServerMessage>().unwrap();
+            if i + 1 < bytes.len() {
+                assert!(result.is_none());
+            } else {
+                decoded = result;
+            }
+        }
+
+        match decoded {
+            Some(ServerMessage::Disconnect {
+                reason: DisconnectReason::InvalidData { detail },
+            }) => assert_eq!(detail.len(), 100_000),
+            _ => panic!("expected a disconnect message"),
+        }
+    }
+
+    #[test]
+    fn chunk_packets_are_palette_compressed() {
+        let bytes = encode(&chunk_message()).unwrap();
+        // A flat array costs at least 4 bytes per cube for the enum tag alone.
+        assert!(bytes.len() * 10 < MAP_HEIGHT * CHUNK_SIZE * CHUNK_SIZE * 4);
+
+        let mut decoder = FrameDecoder::new();
+        decoder.extend(&bytes);
+        match decoder.decode::<ServerMessage>().unwrap() {
+            Some(ServerMessage::Chunk { x, y, chunk, .. }) => {
+                assert_eq!((x, y), (3, -7));
+                assert!(*chunk == layered_chunk());
+            }
+            _ => panic!("expected a chunk message"),
+        }
+    }
+
+    #[test]
+    fn coalesced_messages_are_split() {
+        let mut bytes = encode(&move_message(1)).unwrap();
+        bytes.extend(encode(&chunk_message()).unwrap());
+        bytes.extend(encode(&move_message(2)).unwrap());
+
+        let mut decoder = FrameDecoder::new();
+        decoder.extend(&bytes);
+        assert!(matches!(
+            decoder.decode::<ServerMessage>().unwrap(),
+            Some(ServerMessage::PlayerMove {
+                moved_player_id: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            decoder.decode::<ServerMessage>().unwrap(),
+            Some(ServerMessage::Chunk { .. })
+        ));
+        assert!(matches!(
+            decoder.decode::<ServerMessage>().unwrap(),
+            Some(ServerMessage::PlayerMove {
+                moved_player_id: 2,
+                ..
+            })
+        ));
+        assert!(decoder.decode::<ServerMessage>().unwrap().is_none());
+    }
+
+    #[test]
+    fn oversized_header_is_rejected_before_buffering() {
+        let mut decoder = FrameDecoder::new();
+        decoder.extend(&((MAX_FRAME_SIZE + 1) as u32).to_le_bytes());
+        assert!(matches!(
+            decoder.decode::<ServerMessage>(),
+            Err(FrameError::TooLarge(size)) if size == MAX_FRAME_SIZE + 1
+        ));
+    }
+
+    #[test]
+    fn corrupt_payload_is_a_decode_error() {
+        let mut decoder = FrameDecoder::new();
+        decoder.extend(&4u32.to_le_bytes());
+        decoder.extend(&[0xff; 4]);
+        assert!(matches!(
+            decoder.decode::<ServerMessage>(),
+            Err(FrameError::Decode(_))
+        ));
+    }
+}
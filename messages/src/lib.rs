@@ -1,20 +1,37 @@
-use map_types::{Chunk, Cube};
+use map_types::{Chunk, Cube, Dimension};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub mod codec;
+
+/// Bump whenever the encoding of any message changes.
+pub const PROTOCOL_VERSION: u32 = 8;
 
 #[derive(Serialize, Deserialize)]
 pub enum ClientMessage {
+    /// Must stay the first variant so every build can decode it.
+    Hello {
+        protocol_version: u32,
+        player_name: String,
+        requested_view_distance: u32,
+        /// `BlockRegistry::fingerprint` of the client's block definitions.
+        blocks_fingerprint: u64,
+    },
     Move {
         position: PlayerPosition,
     },
     WatchChunk {
+        dimension: Dimension,
         x: i32,
         y: i32,
     },
     UnwatchChunk {
+        dimension: Dimension,
         x: i32,
         y: i32,
     },
     DestroyBlock {
+        dimension: Dimension,
         chunk_x: i32,
         chunk_y: i32,
         block_x: u32,
@@ -22,6 +39,7 @@ pub enum ClientMessage {
         block_z: u32,
     },
     PutBlock {
+        dimension: Dimension,
         chunk_x: i32,
         chunk_y: i32,
         block_x: u32,
@@ -34,20 +52,29 @@ pub enum ClientMessage {
 /// **중요**: `#[derive(Debug)]` 추가하여, `{:?}` 출력 가능하도록 함
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ServerMessage {
+    /// Must stay the first variant so every build can decode it.
+    Disconnect {
+        reason: DisconnectReason,
+    },
     Init {
         your_player_id: u32,
         your_position: PlayerPosition,
+        view_distance: u32,
+        /// World seed, so the client knows which world it is playing.
+        seed: u64,
     },
     PlayerMove {
         moved_player_id: u32,
         position: PlayerPosition,
     },
     Chunk {
+        dimension: Dimension,
         x: i32,
         y: i32,
         chunk: Box<Chunk>,
     },
     DestroyBlock {
+        dimension: Dimension,
         chunk_x: i32,
         chunk_y: i32,
         block_x: u32,
@@ -55,6 +82,7 @@ pub enum ServerMessage {
         block_z: u32,
     },
     PutBlock {
+        dimension: Dimension,
         chunk_x: i32,
         chunk_y: i32,
         block_x: u32,
@@ -65,6 +93,62 @@ pub enum ServerMessage {
     PlayerAction {
         action: PlayerAction,
     },
+    /// Cubes the server changed on its own, e.g. flowing water, batched per chunk.
+    BlockChanges {
+        dimension: Dimension,
+        chunk_x: i32,
+        chunk_y: i32,
+        changes: Vec<BlockChange>,
+    },
+    /// Moves the receiving player, e.g. through a portal into another dimension.
+    Teleport {
+        position: PlayerPosition,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct BlockChange {
+    pub block_x: u32,
+    pub block_y: u32,
+    pub block_z: u32,
+    pub cube: Cube,
+}
+
+/// New reasons must be appended so older clients can still print them.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum DisconnectReason {
+    IncompatibleProtocol {
+        server_version: u32,
+        client_version: u32,
+    },
+    ExpectedHello,
+    InvalidData {
+        detail: String,
+    },
+    /// Client and server load different block definitions, so block ids would not match.
+    BlockDefinitionsMismatch,
+}
+
+impl fmt::Display for DisconnectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisconnectReason::IncompatibleProtocol {
+                server_version,
+                client_version,
+            } => write!(
+                f,
+                "incompatible client: server speaks protocol {}, client speaks {}",
+                server_version, client_version
+            ),
+            DisconnectReason::ExpectedHello => write!(f, "client did not start with Hello"),
+            DisconnectReason::InvalidData { detail } => {
+                write!(f, "invalid data from client: {}", detail)
+            }
+            DisconnectReason::BlockDefinitionsMismatch => {
+                write!(f, "client and server use different block definitions")
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -77,6 +161,7 @@ pub enum PlayerAction {
 pub enum PlayerPosition {
     NotInWorld,
     InWorld {
+        dimension: Dimension,
         position: [f32; 3],
         horizontal_rotation: f32,
         vertical_rotation: f32,
@@ -0,0 +1,79 @@
+//! Times `grid2` and `grid3` against sampling the same points one at a time, over
+//! grids the size of the ones chunk generation asks for.
+//!
+//! Run with `cargo bench -p noise`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use noise::{Fractal, Noise, NoiseKind};
+
+const SIDE: usize = 32;
+const DEPTH: usize = 16;
+
+/// Nanoseconds per point of the fastest of several rounds of `sample`.
+fn time(points: usize, mut sample: impl FnMut() -> f32) -> f64 {
+    let mut best = Duration::MAX;
+    let mut rounds = 0;
+    let start = Instant::now();
+    while rounds < 20 || start.elapsed() < Duration::from_secs(1) {
+        let round = Instant::now();
+        black_box(sample());
+        best = best.min(round.elapsed());
+        rounds += 1;
+    }
+    best.as_nanos() as f64 / points as f64
+}
+
+fn compare(name: &str, points: usize, single: impl FnMut() -> f32, grid: impl FnMut() -> f32) {
+    let single = time(points, single);
+    let grid = time(points, grid);
+    println!(
+        "{:<14} {:>7.1} ns/point one at a time {:>7.1} ns/point as a grid ({:.1}x)",
+        name,
+        single,
+        grid,
+        single / grid
+    );
+}
+
+fn main() {
+    let axis =
+        |offset: f32, len: usize| -> Vec<f32> { (0..len).map(|i| offset + i as f32).collect() };
+    let (xs, ys, zs) = (axis(-1000.0, SIDE), axis(2000.0, SIDE), axis(40.0, DEPTH));
+
+    for kind in [NoiseKind::Perlin, NoiseKind::OpenSimplex2] {
+        let noise: Noise = Fractal::new(0.02, 1.0).octaves(4).kind(kind).build(42);
+
+        compare(
+            &format!("{:?} 2D", kind),
+            SIDE * SIDE,
+            || {
+                let mut total = 0.0;
+                for &y in &ys {
+                    for &x in &xs {
+                        total += noise.noise2(x, y);
+                    }
+                }
+                total
+            },
+            || noise.grid2(&xs, &ys).iter().sum(),
+        );
+        compare(
+            &format!("{:?} 3D", kind),
+            SIDE * SIDE * DEPTH,
+            || {
+                let mut total = 0.0;
+                for &z in &zs {
+                    for &y in &ys {
+                        for &x in &xs {
+                            total += noise.noise3(x, y, z);
+                        }
+                    }
+                }
+                total
+            },
+            || noise.grid3(&xs, &ys, &zs).iter().sum(),
+        );
+    }
+}
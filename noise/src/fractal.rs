@@ -0,0 +1,166 @@
+//! Layers described as octaves of one noise, each finer and weaker than the last.
+
+use crate::{Noise, NoiseKind, NoiseLayer};
+
+/// How each octave shapes its noise before the octaves are added up.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FractalMode {
+    /// The noise as it is: rolling hills.
+    #[default]
+    Fbm,
+    /// Sharp crests where the noise crosses zero, like mountain ridges.
+    Ridged,
+    /// Rounded bumps with creases where the noise crosses zero, like clouds.
+    Billow,
+}
+
+impl FractalMode {
+    /// Maps a noise value in [-1, 1] back into [-1, 1].
+    pub(crate) fn shape(self, value: f32) -> f32 {
+        match self {
+            FractalMode::Fbm => value,
+            FractalMode::Ridged => 1.0 - 2.0 * value.abs(),
+            FractalMode::Billow => 2.0 * value.abs() - 1.0,
+        }
+    }
+}
+
+/// A builder for the layers of a [`Noise`].
+///
+/// The first octave has the given frequency and amplitude. Every further octave
+/// multiplies the frequency by the lacunarity and the amplitude by the gain, and
+/// offsets the seed by the seed step so octaves do not share their lattice.
+#[derive(Clone, Copy, Debug)]
+pub struct Fractal {
+    frequency: f32,
+    amplitude: f32,
+    octaves: usize,
+    lacunarity: f32,
+    gain: f32,
+    mode: FractalMode,
+    kind: NoiseKind,
+    seed_step: u64,
+}
+
+impl Fractal {
+    pub fn new(frequency: f32, amplitude: f32) -> Fractal {
+        Fractal {
+            frequency,
+            amplitude,
+            octaves: 1,
+            lacunarity: 2.0,
+            gain: 0.5,
+            mode: FractalMode::Fbm,
+            kind: NoiseKind::Perlin,
+            seed_step: 1,
+        }
+    }
+
+    pub fn octaves(self, octaves: usize) -> Fractal {
+        Fractal { octaves, ..self }
+    }
+
+    pub fn lacunarity(self, lacunarity: f32) -> Fractal {
+        Fractal { lacunarity, ..self }
+    }
+
+    pub fn gain(self, gain: f32) -> Fractal {
+        Fractal { gain, ..self }
+    }
+
+    pub fn mode(self, mode: FractalMode) -> Fractal {
+        Fractal { mode, ..self }
+    }
+
+    pub fn kind(self, kind: NoiseKind) -> Fractal {
+        Fractal { kind, ..self }
+    }
+
+    /// Octave `i` samples the lattice of seed `seed + i * step`. A step of 0 puts all
+    /// octaves on the same lattice, as hand-written layers are.
+    pub fn seed_step(self, seed_step: u64) -> Fractal {
+        Fractal { seed_step, ..self }
+    }
+
+    pub fn layers(&self) -> Vec<NoiseLayer> {
+        let mut frequency = self.frequency;
+        let mut amplitude = self.amplitude;
+        (0..self.octaves)
+            .map(|octave| {
+                let layer = NoiseLayer::new(frequency, amplitude)
+                    .with_kind(self.kind)
+                    .with_mode(self.mode)
+                    .with_seed_offset((octave as u64).wrapping_mul(self.seed_step));
+                frequency *= self.lacunarity;
+                amplitude *= self.gain;
+                layer
+            })
+            .collect()
+    }
+
+    pub fn build(&self, seed: u64) -> Noise {
+        Noise::new(&self.layers(), seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn octaves_get_finer_and_weaker() {
+        let layers = Fractal::new(0.01, 0.8)
+            .octaves(4)
+            .lacunarity(3.0)
+            .gain(0.25)
+            .layers();
+        let shape: Vec<(f32, f32, u64)> = layers
+            .iter()
+            .map(|layer| (layer.frequency, layer.amplitude, layer.seed_offset))
+            .collect();
+        let expected = [
+            (0.01, 0.8, 0),
+            (0.03, 0.2, 1),
+            (0.09, 0.05, 2),
+            (0.27, 0.0125, 3),
+        ];
+        assert_eq!(shape.len(), expected.len());
+        for ((f, a, s), (ef, ea, es)) in shape.into_iter().zip(expected) {
+            assert!((f - ef).abs() < 1e-6 && (a - ea).abs() < 1e-6 && s == es);
+        }
+    }
+
+    #[test]
+    fn modes_fold_the_noise_around_zero() {
+        let plain = Fractal::new(0.1, 1.0).build(42);
+        let ridged = Fractal::new(0.1, 1.0).mode(FractalMode::Ridged).build(42);
+        let billow = Fractal::new(0.1, 1.0).mode(FractalMode::Billow).build(42);
+        for i in 0..100 {
+            let (x, y) = (i as f32 * 1.37, i as f32 * -0.71);
+            let value = plain.noise2(x, y);
+            assert_eq!(ridged.noise2(x, y), 1.0 - 2.0 * value.abs());
+            assert_eq!(billow.noise2(x, y), 2.0 * value.abs() - 1.0);
+        }
+    }
+
+    #[test]
+    fn seed_steps_separate_the_octaves() {
+        // 격자를 공유하면 주파수가 같은 두 옥타브의 값이 똑같음
+        let shared = Fractal::new(1.0, 1.0)
+            .octaves(2)
+            .lacunarity(1.0)
+            .gain(1.0)
+            .seed_step(0);
+        let apart = shared.seed_step(1);
+        let sample = |fractal: Fractal| {
+            let layers = fractal.layers();
+            let first = Noise::new(&layers[..1], 42);
+            let second = Noise::new(&layers[1..], 42);
+            (first.noise2(0.3, 0.6), second.noise2(0.3, 0.6))
+        };
+        let (a, b) = sample(shared);
+        assert_eq!(a, b);
+        let (a, b) = sample(apart);
+        assert_ne!(a, b);
+    }
+}
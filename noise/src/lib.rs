@@ -1,11 +1,36 @@
 use std::f32;
 
-const PERMUTATION_SIZE: usize = 256;
+mod fractal;
+mod perlin;
+mod simplex;
+mod warp;
+mod worley;
+
+pub use fractal::{Fractal, FractalMode};
+pub use warp::Warp;
+pub use worley::{Cell, Worley};
+
+// 격자 좌표마다 곱해서 섞는 홀수 상수
+const PRIME_X: u64 = 0x9e37_79b9_7f4a_7c15;
+const PRIME_Y: u64 = 0xc2b2_ae3d_27d4_eb4f;
+const PRIME_Z: u64 = 0x1656_67b1_9e37_79f9;
+const PRIME_W: u64 = 0x27d4_eb2f_1656_67c5;
+
+/// The kind of gradient noise a layer samples.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NoiseKind {
+    #[default]
+    Perlin,
+    OpenSimplex2,
+}
 
 #[derive(Clone, Copy)]
 pub struct NoiseLayer {
     frequency: f32,
     amplitude: f32,
+    kind: NoiseKind,
+    mode: FractalMode,
+    seed_offset: u64,
 }
 
 impl NoiseLayer {
@@ -13,6 +38,26 @@ impl NoiseLayer {
         NoiseLayer {
             frequency,
             amplitude,
+            kind: NoiseKind::Perlin,
+            mode: FractalMode::Fbm,
+            seed_offset: 0,
+        }
+    }
+
+    pub fn with_kind(self, kind: NoiseKind) -> NoiseLayer {
+        NoiseLayer { kind, ..self }
+    }
+
+    pub fn with_mode(self, mode: FractalMode) -> NoiseLayer {
+        NoiseLayer { mode, ..self }
+    }
+
+    /// Samples a lattice of its own instead of the one shared by all layers with the
+    /// default offset of 0.
+    pub fn with_seed_offset(self, seed_offset: u64) -> NoiseLayer {
+        NoiseLayer {
+            seed_offset,
+            ..self
         }
     }
 }
@@ -20,151 +65,360 @@ impl NoiseLayer {
 #[derive(Clone)]
 pub struct Noise {
     layers: Vec<NoiseLayer>,
-    permutation: [u8; PERMUTATION_SIZE * 2],
+    seeds: Vec<u64>,
 }
 
 impl Noise {
     pub fn new(layers: &[NoiseLayer], seed: u64) -> Noise {
-        let mut permutation = [0u8; PERMUTATION_SIZE * 2];
-        let mut p: [u8; PERMUTATION_SIZE] = [0; PERMUTATION_SIZE];
-
-        for (i, v) in p.iter_mut().enumerate() {
-            *v = i as u8
+        Noise {
+            layers: Vec::from(layers),
+            seeds: layers
+                .iter()
+                .map(|layer| mix(seed.wrapping_add(layer.seed_offset)))
+                .collect(),
         }
+    }
 
-        let mut seed = seed;
-        for i in (0..PERMUTATION_SIZE).rev() {
-            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
-            let j = (seed % (i as u64 + 1)) as usize;
-            p.swap(i, j);
+    fn sum(&self, sample: impl Fn(&NoiseLayer, u64) -> f32) -> f32 {
+        let mut total = 0.0;
+        for (layer, &seed) in self.layers.iter().zip(&self.seeds) {
+            total += layer.mode.shape(sample(layer, seed)) * layer.amplitude;
         }
+        total
+    }
 
-        permutation[..PERMUTATION_SIZE].copy_from_slice(&p[..PERMUTATION_SIZE]);
-        permutation[PERMUTATION_SIZE..(PERMUTATION_SIZE + PERMUTATION_SIZE)]
-            .copy_from_slice(&p[..PERMUTATION_SIZE]);
+    pub fn noise2(&self, x: f32, y: f32) -> f32 {
+        self.sum(|layer, seed| {
+            let (x, y) = (x * layer.frequency, y * layer.frequency);
+            match layer.kind {
+                NoiseKind::Perlin => perlin::noise2(seed, x, y),
+                NoiseKind::OpenSimplex2 => simplex::noise2(seed, x, y),
+            }
+        })
+    }
 
-        Noise {
-            layers: Vec::from(layers),
-            permutation,
+    pub fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
+        self.sum(|layer, seed| {
+            let [x, y, z] = [x, y, z].map(|c| c * layer.frequency);
+            match layer.kind {
+                NoiseKind::Perlin => perlin::noise3(seed, x, y, z),
+                NoiseKind::OpenSimplex2 => simplex::noise3(seed, x, y, z),
+            }
+        })
+    }
+
+    /// `noise2` at every combination of `xs` and `ys`, with x changing fastest, giving
+    /// exactly the same values. Each row is sampled `LANES` points at a time; Perlin
+    /// layers also work out the lattice corners of a row once instead of per point,
+    /// which makes them several times faster than single samples. `benches/grid.rs`
+    /// measures both.
+    pub fn grid2(&self, xs: &[f32], ys: &[f32]) -> Vec<f32> {
+        let mut out = vec![0.0; xs.len() * ys.len()];
+        self.fill_rows(
+            xs,
+            &mut out,
+            ys.iter().map(|&y| (y, 0.0)),
+            |layer, seed, xs, (y, _), row| {
+                let y = y * layer.frequency;
+                match layer.kind {
+                    NoiseKind::Perlin => perlin::row2(seed, xs, y, row),
+                    NoiseKind::OpenSimplex2 => simplex::row2(seed, xs, y, row),
+                }
+            },
+        );
+        out
+    }
+
+    /// `noise3` at every combination of `xs`, `ys` and `zs`, with x changing fastest
+    /// and z slowest. See `grid2`.
+    pub fn grid3(&self, xs: &[f32], ys: &[f32], zs: &[f32]) -> Vec<f32> {
+        let mut out = vec![0.0; xs.len() * ys.len() * zs.len()];
+        let rows = zs.iter().flat_map(|&z| ys.iter().map(move |&y| (y, z)));
+        self.fill_rows(xs, &mut out, rows, |layer, seed, xs, (y, z), row| {
+            let (y, z) = (y * layer.frequency, z * layer.frequency);
+            match layer.kind {
+                NoiseKind::Perlin => perlin::row3(seed, xs, y, z, row),
+                NoiseKind::OpenSimplex2 => simplex::row3(seed, xs, y, z, row),
+            }
+        });
+        out
+    }
+
+    /// Adds up the layers row by row; `sample` fills one row of a layer with the raw
+    /// noise along the already scaled `xs`.
+    fn fill_rows(
+        &self,
+        xs: &[f32],
+        out: &mut [f32],
+        rows: impl Iterator<Item = (f32, f32)> + Clone,
+        mut sample: impl FnMut(&NoiseLayer, u64, &[f32], (f32, f32), &mut [f32]),
+    ) {
+        if xs.is_empty() {
+            return;
+        }
+        let mut values = vec![0.0; xs.len()];
+        let mut scaled = vec![0.0; xs.len()];
+        for (layer, &seed) in self.layers.iter().zip(&self.seeds) {
+            for (scaled, x) in scaled.iter_mut().zip(xs) {
+                *scaled = x * layer.frequency;
+            }
+            for (row, totals) in rows.clone().zip(out.chunks_mut(xs.len())) {
+                sample(layer, seed, &scaled, row, &mut values);
+                for (total, &value) in totals.iter_mut().zip(&values) {
+                    *total += layer.mode.shape(value) * layer.amplitude;
+                }
+            }
         }
     }
 
-    fn fade(t: f32) -> f32 {
-        t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+    /// 4D noise, e.g. a 3D field that changes smoothly over time `w`.
+    pub fn noise4(&self, x: f32, y: f32, z: f32, w: f32) -> f32 {
+        self.sum(|layer, seed| {
+            let [x, y, z, w] = [x, y, z, w].map(|c| c * layer.frequency);
+            match layer.kind {
+                NoiseKind::Perlin => perlin::noise4(seed, x, y, z, w),
+                NoiseKind::OpenSimplex2 => simplex::noise4(seed, x, y, z, w),
+            }
+        })
     }
+}
 
-    fn lerp(t: f32, a: f32, b: f32) -> f32 {
-        a + t * (b - a)
+/// How many points of a row are sampled together.
+const LANES: usize = 8;
+
+/// Calls `sample` on `xs` and `out` a full group of `LANES` at a time, padding the
+/// last group with copies of the last point. The loops inside `sample` then have a
+/// fixed length and no branches, so the compiler turns them into vector instructions.
+fn for_lanes(
+    xs: &[f32],
+    out: &mut [f32],
+    mut sample: impl FnMut(&[f32; LANES], &mut [f32; LANES]),
+) {
+    let mut groups = xs.chunks_exact(LANES);
+    let mut outs = out.chunks_exact_mut(LANES);
+    for (xs, out) in (&mut groups).zip(&mut outs) {
+        sample(xs.try_into().unwrap(), out.try_into().unwrap());
     }
+    let (rest, out) = (groups.remainder(), outs.into_remainder());
+    if let Some(&last) = rest.last() {
+        let mut padded = [last; LANES];
+        padded[..rest.len()].copy_from_slice(rest);
+        let mut values = [0.0; LANES];
+        sample(&padded, &mut values);
+        out.copy_from_slice(&values[..rest.len()]);
+    }
+}
 
-    fn grad2(hash: u8, x: f32, y: f32) -> f32 {
-        let h = hash & 3;
-        let u = if h & 2 == 0 { x } else { -x };
-        let v = if h & 1 == 0 { y } else { -y };
-        u + v
+/// Adding this to a number in [0, 2^23) and subtracting it again rounds the number to
+/// an integer, since floats from 2^23 up have no fraction bits left.
+const ROUNDING: f32 = 8_388_608.0;
+
+/// `f32::floor` using only plain float arithmetic, which, unlike the library call
+/// `f32::floor` becomes on CPUs without SSE4.1, the compiler can vectorize.
+fn floor(x: f32) -> f32 {
+    let nearest = ((x.abs() + ROUNDING) - ROUNDING).copysign(x);
+    let below = if nearest > x { nearest - 1.0 } else { nearest };
+    if x.abs() < ROUNDING {
+        below
+    } else {
+        x
     }
+}
 
-    pub fn noise2(&self, x: f32, y: f32) -> f32 {
-        let mut total = 0.0;
+/// `f32::round` in the same way as `floor`.
+fn round(x: f32) -> f32 {
+    let a = x.abs();
+    let nearest = (a + ROUNDING) - ROUNDING;
+    let below = if nearest > a { nearest - 1.0 } else { nearest };
+    let rounded = if a - below >= 0.5 { below + 1.0 } else { below };
+    if a < ROUNDING {
+        rounded.copysign(x)
+    } else {
+        x
+    }
+}
 
-        for layer in &self.layers {
-            let xf = x * layer.frequency;
-            let yf = y * layer.frequency;
+/// Hash of a lattice point. Every `i32` coordinate gets its own value, so the noise
+/// never repeats within the world.
+fn hash2(seed: u64, x: i32, y: i32) -> u64 {
+    mix(seed ^ (x as u64).wrapping_mul(PRIME_X) ^ (y as u64).wrapping_mul(PRIME_Y))
+}
 
-            let xi = (xf.floor() as i32) & 255;
-            let yi = (yf.floor() as i32) & 255;
+fn hash3(seed: u64, x: i32, y: i32, z: i32) -> u64 {
+    mix(seed
+        ^ (x as u64).wrapping_mul(PRIME_X)
+        ^ (y as u64).wrapping_mul(PRIME_Y)
+        ^ (z as u64).wrapping_mul(PRIME_Z))
+}
 
-            let xf = xf - xf.floor();
-            let yf = yf - yf.floor();
+fn hash4(seed: u64, x: i32, y: i32, z: i32, w: i32) -> u64 {
+    mix(seed
+        ^ (x as u64).wrapping_mul(PRIME_X)
+        ^ (y as u64).wrapping_mul(PRIME_Y)
+        ^ (z as u64).wrapping_mul(PRIME_Z)
+        ^ (w as u64).wrapping_mul(PRIME_W))
+}
 
-            let u = Noise::fade(xf);
-            let v = Noise::fade(yf);
+/// The splitmix64 finalizer: every input bit affects every output bit.
+fn mix(mut h: u64) -> u64 {
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
 
-            let a = self.permutation[xi as usize] as usize + yi as usize;
-            let aa = self.permutation[a] as usize;
-            let ab = self.permutation[a + 1] as usize;
-            let b = self.permutation[xi as usize + 1] as usize + yi as usize;
-            let ba = self.permutation[b] as usize;
-            let bb = self.permutation[b + 1] as usize;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            let grad_aa = Noise::grad2(self.permutation[aa], xf, yf);
-            let grad_ba = Noise::grad2(self.permutation[ba], xf - 1.0, yf);
-            let grad_ab = Noise::grad2(self.permutation[ab], xf, yf - 1.0);
-            let grad_bb = Noise::grad2(self.permutation[bb], xf - 1.0, yf - 1.0);
+    fn unit(seed: u64) -> Noise {
+        Noise::new(&[NoiseLayer::new(1.0, 1.0)], seed)
+    }
 
-            let lerp_x1 = Noise::lerp(u, grad_aa, grad_ba);
-            let lerp_x2 = Noise::lerp(u, grad_ab, grad_bb);
-            let lerp_y = Noise::lerp(v, lerp_x1, lerp_x2);
+    // 격자 한 칸 안의 여러 점을 찍어 칸의 모양을 비교함
+    fn cell2(noise: &Noise, x: i32, y: i32) -> Vec<f32> {
+        (0..16)
+            .map(|i| {
+                let (dx, dy) = ((i % 4) as f32 * 0.25 + 0.1, (i / 4) as f32 * 0.25 + 0.1);
+                noise.noise2(x as f32 + dx, y as f32 + dy)
+            })
+            .collect()
+    }
+
+    fn cell3(noise: &Noise, x: i32, y: i32, z: i32) -> Vec<f32> {
+        (0..8)
+            .map(|i| {
+                let d = |bit: i32| if i & bit == 0 { 0.3 } else { 0.7 };
+                noise.noise3(x as f32 + d(1), y as f32 + d(2), z as f32 + d(4))
+            })
+            .collect()
+    }
 
-            total += lerp_y * layer.amplitude;
+    #[test]
+    fn noise_does_not_repeat_every_256_cells() {
+        let noise = unit(42);
+        for start in [0, -3, 1000, -70_000] {
+            for period in [256, 512, 65_536] {
+                assert_ne!(cell2(&noise, start, 7), cell2(&noise, start + period, 7));
+                assert_ne!(cell2(&noise, 7, start), cell2(&noise, 7, start + period));
+                assert_ne!(
+                    cell3(&noise, start, 5, 9),
+                    cell3(&noise, start + period, 5, 9)
+                );
+                assert_ne!(
+                    cell3(&noise, 5, 9, start),
+                    cell3(&noise, 5, 9, start + period)
+                );
+            }
         }
+    }
 
-        total
+    #[test]
+    fn lattice_hashes_cover_the_whole_i32_range() {
+        let noise = unit(42);
+        // 멀리 떨어진 격자점도 서로 다른 값을 가져야 함
+        let far = [
+            i32::MIN,
+            -(1 << 24),
+            -256,
+            0,
+            256,
+            1 << 16,
+            1 << 24,
+            i32::MAX - 1,
+        ];
+        let hashes: Vec<Vec<u64>> = far
+            .iter()
+            .map(|&x| (0..8).map(|y| hash2(noise.seeds[0], x, y)).collect())
+            .collect();
+        for (i, a) in hashes.iter().enumerate() {
+            for b in &hashes[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        // 좌표 끝에서도 이웃 칸으로 넘어가며 패닉하지 않음
+        let edge = i32::MAX as f32;
+        assert!(noise.noise2(edge, -edge).is_finite());
+        assert!(noise.noise3(edge, -edge, edge).is_finite());
     }
 
-    fn grad3(hash: u8, x: f32, y: f32, z: f32) -> f32 {
-        let h = hash & 15;
-        let u = if h < 8 { x } else { y };
-        let v = if h < 4 {
-            y
-        } else if h == 12 || h == 14 {
-            x
-        } else {
-            z
-        };
-        let u_sign = if h & 1 == 0 { u } else { -u };
-        let v_sign = if h & 2 == 0 { v } else { -v };
-        u_sign + v_sign
+    #[test]
+    fn seeds_give_different_noise() {
+        assert_eq!(cell2(&unit(1), 3, 4), cell2(&unit(1), 3, 4));
+        assert_ne!(cell2(&unit(1), 3, 4), cell2(&unit(2), 3, 4));
     }
 
-    pub fn noise3(&self, x: f32, y: f32, z: f32) -> f32 {
-        let mut total = 0.0;
+    #[test]
+    fn every_kind_is_bounded_and_continuous() {
+        for kind in [NoiseKind::Perlin, NoiseKind::OpenSimplex2] {
+            let noise = Noise::new(&[NoiseLayer::new(0.37, 1.0).with_kind(kind)], 42);
+            let (mut highest, mut jump) = (0.0f32, 0.0f32);
+            for i in 0..20_000 {
+                let p = [
+                    i as f32 * 0.731,
+                    i as f32 * -0.413,
+                    i as f32 * 0.157,
+                    i as f32 * 0.293,
+                ];
+                let q = p.map(|c| c + 0.001);
+                let values = [
+                    (noise.noise2(p[0], p[1]), noise.noise2(q[0], q[1])),
+                    (
+                        noise.noise3(p[0], p[1], p[2]),
+                        noise.noise3(q[0], q[1], q[2]),
+                    ),
+                    (
+                        noise.noise4(p[0], p[1], p[2], p[3]),
+                        noise.noise4(q[0], q[1], q[2], q[3]),
+                    ),
+                ];
+                for (a, b) in values {
+                    highest = highest.max(a.abs());
+                    jump = jump.max((a - b).abs());
+                }
+            }
+            assert!(highest <= 1.0, "{:?} reaches {}", kind, highest);
+            assert!(highest > 0.6, "{:?} only reaches {}", kind, highest);
+            assert!(jump < 0.01, "{:?} jumps by {}", kind, jump);
+        }
+    }
 
-        for layer in &self.layers {
-            let xf = x * layer.frequency;
-            let yf = y * layer.frequency;
-            let zf = z * layer.frequency;
-
-            let xi = (xf.floor() as i32) & 255;
-            let yi = (yf.floor() as i32) & 255;
-            let zi = (zf.floor() as i32) & 255;
-
-            let xf = xf - xf.floor();
-            let yf = yf - yf.floor();
-            let zf = zf - zf.floor();
-
-            let u = Noise::fade(xf);
-            let v = Noise::fade(yf);
-            let w = Noise::fade(zf);
-
-            let a = self.permutation[xi as usize] as usize + yi as usize;
-            let aa = self.permutation[a] as usize + zi as usize;
-            let ab = self.permutation[a + 1] as usize + zi as usize;
-            let b = self.permutation[xi as usize + 1] as usize + yi as usize;
-            let ba = self.permutation[b] as usize + zi as usize;
-            let bb = self.permutation[b + 1] as usize + zi as usize;
-
-            let grad_aa = Noise::grad3(self.permutation[aa], xf, yf, zf);
-            let grad_ba = Noise::grad3(self.permutation[ba], xf - 1.0, yf, zf);
-            let grad_ab = Noise::grad3(self.permutation[ab], xf, yf - 1.0, zf);
-            let grad_bb = Noise::grad3(self.permutation[bb], xf - 1.0, yf - 1.0, zf);
-
-            let grad_aa1 = Noise::grad3(self.permutation[aa + 1], xf, yf, zf - 1.0);
-            let grad_ba1 = Noise::grad3(self.permutation[ba + 1], xf - 1.0, yf, zf - 1.0);
-            let grad_ab1 = Noise::grad3(self.permutation[ab + 1], xf, yf - 1.0, zf - 1.0);
-            let grad_bb1 = Noise::grad3(self.permutation[bb + 1], xf - 1.0, yf - 1.0, zf - 1.0);
-
-            let lerp_x1 = Noise::lerp(u, grad_aa, grad_ba);
-            let lerp_x2 = Noise::lerp(u, grad_ab, grad_bb);
-            let lerp_y1 = Noise::lerp(v, lerp_x1, lerp_x2);
-
-            let lerp_x3 = Noise::lerp(u, grad_aa1, grad_ba1);
-            let lerp_x4 = Noise::lerp(u, grad_ab1, grad_bb1);
-            let lerp_y2 = Noise::lerp(v, lerp_x3, lerp_x4);
-
-            total += Noise::lerp(w, lerp_y1, lerp_y2) * layer.amplitude;
+    #[test]
+    fn floor_and_round_match_the_standard_ones() {
+        let mut values = vec![0.0, -0.0, 0.5, -0.5, 1.5, -2.5, 8_388_607.5, -8_388_607.5];
+        values.extend([1e7, -1e7, 3e9, -3e9, f32::MAX, f32::MIN, f32::INFINITY]);
+        values.extend((0..10_000).map(|i| (i as f32 - 5000.0) * 0.173));
+        for x in values {
+            assert_eq!(floor(x).to_bits(), x.floor().to_bits(), "floor({})", x);
+            assert_eq!(round(x).to_bits(), x.round().to_bits(), "round({})", x);
         }
+        assert!(floor(f32::NAN).is_nan() && round(f32::NAN).is_nan());
+    }
 
-        total
+    #[test]
+    fn grids_match_single_samples() {
+        let layers = [
+            NoiseLayer::new(0.05, 0.6),
+            NoiseLayer::new(0.31, 0.3).with_mode(FractalMode::Ridged),
+            NoiseLayer::new(0.07, 0.1)
+                .with_kind(NoiseKind::OpenSimplex2)
+                .with_seed_offset(9),
+        ];
+        let noise = Noise::new(&layers, 42);
+        let xs: Vec<f32> = (0..19).map(|i| -40.0 + i as f32 * 4.3).collect();
+        let ys: Vec<f32> = (0..7).map(|j| 1000.0 - j as f32 * 0.77).collect();
+        let zs = [0.0, -13.5, 420.0];
+
+        let flat = noise.grid2(&xs, &ys);
+        let solid = noise.grid3(&xs, &ys, &zs);
+        for (j, &y) in ys.iter().enumerate() {
+            for (i, &x) in xs.iter().enumerate() {
+                assert_eq!(flat[j * xs.len() + i], noise.noise2(x, y));
+                for (k, &z) in zs.iter().enumerate() {
+                    let index = (k * ys.len() + j) * xs.len() + i;
+                    assert_eq!(solid[index], noise.noise3(x, y, z));
+                }
+            }
+        }
+        assert!(noise.grid3(&[], &ys, &zs).is_empty());
     }
 }
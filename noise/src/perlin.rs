@@ -0,0 +1,265 @@
+//! Classic gradient noise on a cubic lattice.
+
+use crate::{floor, for_lanes, hash2, hash3, hash4, LANES};
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+/// The gradients take the top byte of the lattice hash, as they always have, so the
+/// terrain of existing worlds stays the same.
+fn top_byte(hash: u64) -> u64 {
+    hash >> 56
+}
+
+/// The gradients (±1, ±1), picked by the lowest two bits of the top byte of a hash.
+const GRADIENTS_2D: [[f32; 2]; 4] = [[1.0, 1.0], [1.0, -1.0], [-1.0, 1.0], [-1.0, -1.0]];
+
+/// The 12 vectors towards the middle of the edges of the cube, four of them twice,
+/// picked by the lowest four bits of the top byte of a hash.
+const GRADIENTS_3D: [[f32; 3]; 16] = [
+    [1.0, 1.0, 0.0],
+    [-1.0, 1.0, 0.0],
+    [1.0, -1.0, 0.0],
+    [-1.0, -1.0, 0.0],
+    [1.0, 0.0, 1.0],
+    [-1.0, 0.0, 1.0],
+    [1.0, 0.0, -1.0],
+    [-1.0, 0.0, -1.0],
+    [0.0, 1.0, 1.0],
+    [0.0, -1.0, 1.0],
+    [0.0, 1.0, -1.0],
+    [0.0, -1.0, -1.0],
+    [1.0, 1.0, 0.0],
+    [0.0, -1.0, 1.0],
+    [-1.0, 1.0, 0.0],
+    [0.0, -1.0, -1.0],
+];
+
+/// A lattice corner seen from a row of points along x: the x part of its gradient,
+/// and what the y and z parts add for the row. At `xf` from the corner along x it adds
+/// `gx * xf + k` to the noise, exactly what the whole dot product gives, since
+/// every gradient has a zero part and adding a zero changes nothing.
+type Corner = [f32; 2];
+
+fn along([gx, k]: Corner, xf: f32) -> f32 {
+    gx * xf + k
+}
+
+/// The corners (x, y) and (x, y + 1) of a 2D cell, seen from offset `yf` into it.
+fn edge2(seed: u64, xi: i32, yi: i32, yf: f32) -> [Corner; 2] {
+    let corner = |cy: i32, dy: f32| {
+        let [gx, gy] = GRADIENTS_2D[(top_byte(hash2(seed, xi, cy)) & 3) as usize];
+        [gx, gy * dy]
+    };
+    [corner(yi, yf), corner(yi.wrapping_add(1), yf - 1.0)]
+}
+
+/// The corners (x, y, z), (x, y + 1, z), (x, y, z + 1) and (x, y + 1, z + 1) of a 3D
+/// cell, seen from offset (`yf`, `zf`) into it.
+fn face3(seed: u64, xi: i32, [yi, zi]: [i32; 2], [yf, zf]: [f32; 2]) -> [Corner; 4] {
+    let (yj, zj) = (yi.wrapping_add(1), zi.wrapping_add(1));
+    let corner = |cy: i32, cz: i32, dy: f32, dz: f32| {
+        let [gx, gy, gz] = GRADIENTS_3D[(top_byte(hash3(seed, xi, cy, cz)) & 15) as usize];
+        [gx, gy * dy + gz * dz]
+    };
+    [
+        corner(yi, zi, yf, zf),
+        corner(yj, zi, yf - 1.0, zf),
+        corner(yi, zj, yf, zf - 1.0),
+        corner(yj, zj, yf - 1.0, zf - 1.0),
+    ]
+}
+
+pub fn noise2(seed: u64, x: f32, y: f32) -> f32 {
+    let (xi, yi) = (x.floor() as i32, y.floor() as i32);
+    let yf = y - y.floor();
+    let left = edge2(seed, xi, yi, yf);
+    let right = edge2(seed, xi.wrapping_add(1), yi, yf);
+    blend2(&left, &right, x - x.floor(), fade(yf))
+}
+
+/// Samples a row of points that share `y`. The corners of every cell the row touches
+/// are worked out beforehand, so no point hashes anything.
+pub fn row2(seed: u64, xs: &[f32], y: f32, out: &mut [f32]) {
+    let yi = floor(y) as i32;
+    let yf = y - floor(y);
+    let v = fade(yf);
+    let Some(edges) = Lattice::new(xs, |xi| edge2(seed, xi, yi, yf)) else {
+        for (value, &x) in out.iter_mut().zip(xs) {
+            *value = noise2(seed, x, y);
+        }
+        return;
+    };
+    for_lanes(xs, out, |xs, out| {
+        let (index, xf) = edges.locate(xs);
+        for l in 0..LANES {
+            let (left, right) = edges.around(index[l]);
+            out[l] = blend2(left, right, xf[l], v);
+        }
+    });
+}
+
+/// Interpolates between the cell corners, given the offset into the cell along x and
+/// the faded offset along y.
+fn blend2(left: &[Corner; 2], right: &[Corner; 2], xf: f32, v: f32) -> f32 {
+    let u = fade(xf);
+
+    let grad_aa = along(left[0], xf);
+    let grad_ba = along(right[0], xf - 1.0);
+    let grad_ab = along(left[1], xf);
+    let grad_bb = along(right[1], xf - 1.0);
+
+    let lerp_x1 = lerp(u, grad_aa, grad_ba);
+    let lerp_x2 = lerp(u, grad_ab, grad_bb);
+    lerp(v, lerp_x1, lerp_x2)
+}
+
+pub fn noise3(seed: u64, x: f32, y: f32, z: f32) -> f32 {
+    let [xi, yi, zi] = [x, y, z].map(|c| c.floor() as i32);
+    let offset = [y - y.floor(), z - z.floor()];
+    let left = face3(seed, xi, [yi, zi], offset);
+    let right = face3(seed, xi.wrapping_add(1), [yi, zi], offset);
+    blend3(&left, &right, x - x.floor(), offset.map(fade))
+}
+
+/// Samples a row of points that share `y` and `z`. See `row2`.
+pub fn row3(seed: u64, xs: &[f32], y: f32, z: f32, out: &mut [f32]) {
+    let cell = [floor(y) as i32, floor(z) as i32];
+    let offset = [y - floor(y), z - floor(z)];
+    let faded = offset.map(fade);
+    let Some(faces) = Lattice::new(xs, |xi| face3(seed, xi, cell, offset)) else {
+        for (value, &x) in out.iter_mut().zip(xs) {
+            *value = noise3(seed, x, y, z);
+        }
+        return;
+    };
+    for_lanes(xs, out, |xs, out| {
+        let (index, xf) = faces.locate(xs);
+        for l in 0..LANES {
+            let (left, right) = faces.around(index[l]);
+            out[l] = blend3(left, right, xf[l], faded);
+        }
+    });
+}
+
+fn blend3(left: &[Corner; 4], right: &[Corner; 4], xf: f32, [v, w]: [f32; 2]) -> f32 {
+    let u = fade(xf);
+
+    let grad_aa = along(left[0], xf);
+    let grad_ba = along(right[0], xf - 1.0);
+    let grad_ab = along(left[1], xf);
+    let grad_bb = along(right[1], xf - 1.0);
+
+    let grad_aa1 = along(left[2], xf);
+    let grad_ba1 = along(right[2], xf - 1.0);
+    let grad_ab1 = along(left[3], xf);
+    let grad_bb1 = along(right[3], xf - 1.0);
+
+    let lerp_x1 = lerp(u, grad_aa, grad_ba);
+    let lerp_x2 = lerp(u, grad_ab, grad_bb);
+    let lerp_y1 = lerp(v, lerp_x1, lerp_x2);
+
+    let lerp_x3 = lerp(u, grad_aa1, grad_ba1);
+    let lerp_x4 = lerp(u, grad_ab1, grad_bb1);
+    let lerp_y2 = lerp(v, lerp_x3, lerp_x4);
+
+    lerp(w, lerp_y1, lerp_y2)
+}
+
+/// The corners of every lattice column along x that a row of points touches, from
+/// the column of the leftmost point to the one right of the rightmost.
+struct Lattice<T> {
+    first: i32,
+    columns: Vec<T>,
+}
+
+impl<T> Lattice<T> {
+    /// `None` if the points are so far apart that most of the columns would go unused,
+    /// or not finite.
+    fn new(xs: &[f32], column: impl Fn(i32) -> T) -> Option<Lattice<T>> {
+        let (low, high) = xs
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), &x| {
+                (low.min(x), high.max(x))
+            });
+        if !(high - low <= 2.0 * xs.len() as f32 && low.is_finite() && high.is_finite()) {
+            return None;
+        }
+        let first = floor(low) as i32;
+        let count = floor(high) as i32 - first + 2;
+        Some(Lattice {
+            first,
+            columns: (0..count).map(|i| column(first.wrapping_add(i))).collect(),
+        })
+    }
+
+    /// The column left of each point and how far into its cell the point is.
+    fn locate(&self, xs: &[f32; LANES]) -> ([usize; LANES], [f32; LANES]) {
+        let base = xs.map(floor);
+        // NaN인 점도 표 안을 가리키게 함
+        let last = self.columns.len() - 2;
+        let index = base.map(|b| ((b as i32).wrapping_sub(self.first) as usize).min(last));
+        (index, std::array::from_fn(|l| xs[l] - base[l]))
+    }
+
+    /// Column `index` and the one right of it.
+    fn around(&self, index: usize) -> (&T, &T) {
+        (&self.columns[index], &self.columns[index + 1])
+    }
+}
+
+/// One of the 32 vectors pointing at the middle of an edge of the 4D cube, i.e. with
+/// one coordinate zero and the other three ±1.
+fn grad4(hash: u64, p: [f32; 4]) -> f32 {
+    let hash = top_byte(hash);
+    let zero = (hash >> 3 & 3) as usize;
+    let mut signs = hash;
+    let mut total = 0.0;
+    for (axis, c) in p.into_iter().enumerate() {
+        if axis == zero {
+            continue;
+        }
+        total += if signs & 1 == 0 { c } else { -c };
+        signs >>= 1;
+    }
+    total
+}
+
+pub fn noise4(seed: u64, x: f32, y: f32, z: f32, w: f32) -> f32 {
+    let base = [x, y, z, w].map(floor);
+    let cell = base.map(|c| c as i32);
+    let offset = [x - base[0], y - base[1], z - base[2], w - base[3]];
+
+    // 꼭짓점 번호의 i번째 비트가 i축 방향으로 한 칸 옮긴 것인지를 나타냄
+    let mut values = [0.0; 16];
+    for (corner, value) in values.iter_mut().enumerate() {
+        let step = |axis: usize| (corner >> axis & 1) as i32;
+        let hash = hash4(
+            seed,
+            cell[0].wrapping_add(step(0)),
+            cell[1].wrapping_add(step(1)),
+            cell[2].wrapping_add(step(2)),
+            cell[3].wrapping_add(step(3)),
+        );
+        *value = grad4(
+            hash,
+            std::array::from_fn(|axis| offset[axis] - step(axis) as f32),
+        );
+    }
+
+    // 한 축씩 보간해 꼭짓점 수를 반으로 줄여감
+    let mut count = values.len();
+    for t in offset.map(fade) {
+        count /= 2;
+        for i in 0..count {
+            values[i] = lerp(t, values[2 * i], values[2 * i + 1]);
+        }
+    }
+    // 2D, 3D와 비슷하게 대략 [-1, 1] 범위가 되도록 줄임
+    values[0] * 0.84
+}
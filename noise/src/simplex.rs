@@ -0,0 +1,250 @@
+//! OpenSimplex2 gradient noise. Instead of interpolating between the corners of a
+//! cube it sums radial contributions of nearby lattice points, so it has none of the
+//! axis-aligned ridges of Perlin noise.
+
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use crate::{floor, for_lanes, hash2, hash3, hash4, round};
+
+const SKEW_2D: f32 = 0.366_025_4;
+const UNSKEW_2D: f32 = -0.211_324_87;
+const RADIUS_2D: f32 = 0.5;
+const NORMALIZER_2D: f32 = 99.2;
+
+const RADIUS_3D: f32 = 0.6;
+const NORMALIZER_3D: f32 = 39.0;
+/// Separates the hashes of the two halves of the body-centred cubic lattice.
+const SEED_FLIP_3D: u64 = 0x51f8_a3c2_7e4d_96b1;
+
+const SKEW_4D: f32 = 0.309_017;
+const UNSKEW_4D: f32 = -0.138_196_6;
+const RADIUS_4D: f32 = 0.5;
+const NORMALIZER_4D: f32 = 105.0;
+
+const S: f32 = FRAC_1_SQRT_2;
+const GRADIENTS_2D: [[f32; 2]; 24] = [
+    [1.0, 0.0],
+    [0.965_925_8, 0.258_819],
+    [0.866_025_4, 0.5],
+    [S, S],
+    [0.5, 0.866_025_4],
+    [0.258_819, 0.965_925_8],
+    [0.0, 1.0],
+    [-0.258_819, 0.965_925_8],
+    [-0.5, 0.866_025_4],
+    [-S, S],
+    [-0.866_025_4, 0.5],
+    [-0.965_925_8, 0.258_819],
+    [-1.0, 0.0],
+    [-0.965_925_8, -0.258_819],
+    [-0.866_025_4, -0.5],
+    [-S, -S],
+    [-0.5, -0.866_025_4],
+    [-0.258_819, -0.965_925_8],
+    [0.0, -1.0],
+    [0.258_819, -0.965_925_8],
+    [0.5, -0.866_025_4],
+    [S, -S],
+    [0.866_025_4, -0.5],
+    [0.965_925_8, -0.258_819],
+];
+
+/// Picks a gradient with the high bits of `hash`; the low bits feed the signs.
+fn pick(hash: u64, count: usize) -> usize {
+    ((hash >> 32) % count as u64) as usize
+}
+
+fn grad2(hash: u64, x: f32, y: f32) -> f32 {
+    let [gx, gy] = GRADIENTS_2D[pick(hash, GRADIENTS_2D.len())];
+    gx * x + gy * y
+}
+
+/// Directions towards the 12 edges and 8 corners of the cube, scaled to unit length.
+fn grad3(hash: u64, x: f32, y: f32, z: f32) -> f32 {
+    // 두 축에 걸친 모서리 방향 세 묶음과, 세 축 모두에 걸친 꼭짓점 방향 한 묶음.
+    // 묶음을 표에서 골라 방향마다 갈라지는 분기를 없앰
+    const GROUPS: [([usize; 2], f32, f32); 4] = [
+        ([0, 1], 0.0, FRAC_1_SQRT_2),
+        ([0, 2], 0.0, FRAC_1_SQRT_2),
+        ([1, 2], 0.0, FRAC_1_SQRT_2),
+        ([0, 1], 1.0, 0.577_350_3),
+    ];
+    let sign = |bit: u64, c: f32| if hash >> bit & 1 == 0 { c } else { -c };
+    let ([u, v], corner, scale) = GROUPS[(pick(hash, 20) / 4).min(3)];
+    let p = [x, y, z];
+    (sign(0, p[u]) + sign(1, p[v]) + sign(2, z) * corner) * scale
+}
+
+/// The 32 directions towards the middle of the edges of the 4D cube.
+fn grad4(hash: u64, p: [f32; 4]) -> f32 {
+    let zero = pick(hash, 4);
+    let mut total = 0.0;
+    for (axis, c) in p.into_iter().enumerate() {
+        if axis != zero {
+            total += if hash >> axis & 1 == 0 { c } else { -c };
+        }
+    }
+    total * 0.577_350_3
+}
+
+fn falloff(a: f32) -> f32 {
+    let a = a * a;
+    a * a
+}
+
+// 반경 밖의 꼭짓점도 건너뛰지 않고 0을 더함. 점마다 갈라지는 분기가 없어야
+// row2, row3에서 여러 점을 한꺼번에 계산할 수 있음
+pub fn noise2(seed: u64, x: f32, y: f32) -> f32 {
+    // 정삼각형 격자를 정사각형 격자로 펴서 가장 가까운 세 꼭짓점을 찾음
+    let s = (x + y) * SKEW_2D;
+    let (xs, ys) = (x + s, y + s);
+    let (xsb, ysb) = (floor(xs), floor(ys));
+    let (xi, yi) = (xsb as i32, ysb as i32);
+    let (xsi, ysi) = (xs - xsb, ys - ysb);
+    let t = (xsi + ysi) * UNSKEW_2D;
+    let (x0, y0) = (xsi + t, ysi + t);
+
+    let middle = if xsi > ysi { (1, 0) } else { (0, 1) };
+    let mut value = 0.0;
+    for (i, j) in [(0, 0), middle, (1, 1)] {
+        let dx = x0 - i as f32 - (i + j) as f32 * UNSKEW_2D;
+        let dy = y0 - j as f32 - (i + j) as f32 * UNSKEW_2D;
+        let a = RADIUS_2D - dx * dx - dy * dy;
+        let hash = hash2(seed, xi.wrapping_add(i), yi.wrapping_add(j));
+        value += falloff(a.max(0.0)) * grad2(hash, dx, dy);
+    }
+    value * NORMALIZER_2D
+}
+
+/// `noise2` along a row of points that share `y`.
+pub fn row2(seed: u64, xs: &[f32], y: f32, out: &mut [f32]) {
+    for_lanes(xs, out, |xs, out| {
+        for (value, &x) in out.iter_mut().zip(xs) {
+            *value = noise2(seed, x, y);
+        }
+    });
+}
+
+pub fn noise3(seed: u64, x: f32, y: f32, z: f32) -> f32 {
+    // 축이 대각선을 향하도록 돌려서 격자가 눈에 띄지 않게 함
+    let r = (x + y + z) * (2.0 / 3.0);
+    let p = [r - x, r - y, r - z];
+
+    // 체심입방격자는 반 칸 어긋난 두 입방격자로 이루어짐. 각 격자에서 가장 가까운
+    // 점과, 그다음으로 가까울 수 있는 이웃 하나를 더함.
+    let rounded = p.map(round);
+    let mut cell = rounded.map(|c| c as i32);
+    let mut offset = [p[0] - rounded[0], p[1] - rounded[1], p[2] - rounded[2]];
+    let mut seed = seed;
+    let mut a = RADIUS_3D - offset.iter().map(|c| c * c).sum::<f32>();
+    let mut value = 0.0;
+    for half in 0..2 {
+        value += falloff(a.max(0.0))
+            * grad3(
+                hash3(seed, cell[0], cell[1], cell[2]),
+                offset[0],
+                offset[1],
+                offset[2],
+            );
+        // 가장 멀리 벗어난 축. 같으면 뒤의 축
+        let distance = offset.map(f32::abs);
+        let mut axis = 0;
+        for i in 1..3 {
+            if distance[i] >= distance[axis] {
+                axis = i;
+            }
+        }
+        let b = a + 2.0 * distance[axis] - 1.0;
+        let towards = if offset[axis] > 0.0 { 1 } else { -1 };
+        let step = |i: usize| if i == axis { towards } else { 0 };
+        let neighbour: [i32; 3] = std::array::from_fn(|i| cell[i].wrapping_add(step(i)));
+        let d: [f32; 3] = std::array::from_fn(|i| offset[i] - step(i) as f32);
+        value += falloff(b.max(0.0))
+            * grad3(
+                hash3(seed, neighbour[0], neighbour[1], neighbour[2]),
+                d[0],
+                d[1],
+                d[2],
+            );
+        if half == 1 {
+            break;
+        }
+
+        // 다른 쪽 격자에서는 (k - 0.5)에 있는 점을 k로 셈
+        for axis in 0..3 {
+            let up = offset[axis] > 0.0;
+            cell[axis] = cell[axis].wrapping_add(up as i32);
+            offset[axis] += if up { -0.5 } else { 0.5 };
+        }
+        a = RADIUS_3D - offset.iter().map(|c| c * c).sum::<f32>();
+        seed ^= SEED_FLIP_3D;
+    }
+    value * NORMALIZER_3D
+}
+
+/// `noise3` along a row of points that share `y` and `z`.
+pub fn row3(seed: u64, xs: &[f32], y: f32, z: f32, out: &mut [f32]) {
+    for_lanes(xs, out, |xs, out| {
+        for (value, &x) in out.iter_mut().zip(xs) {
+            *value = noise3(seed, x, y, z);
+        }
+    });
+}
+
+pub fn noise4(seed: u64, x: f32, y: f32, z: f32, w: f32) -> f32 {
+    let p = [x, y, z, w];
+    let s = p.iter().sum::<f32>() * SKEW_4D;
+    let skewed = p.map(|c| c + s);
+    let base = skewed.map(floor);
+    let cell = base.map(|c| c as i32);
+    let inside: [f32; 4] = std::array::from_fn(|i| skewed[i] - base[i]);
+    let t = inside.iter().sum::<f32>() * UNSKEW_4D;
+    let origin = inside.map(|c| c + t);
+
+    // 좌표가 큰 축부터 한 칸씩 옮기며 단체의 다섯 꼭짓점을 지남
+    let mut order = [0, 1, 2, 3];
+    order.sort_by(|&i, &j| inside[j].total_cmp(&inside[i]));
+    let mut step = [0i32; 4];
+    let mut value = 0.0;
+    for corner in 0..5 {
+        if corner > 0 {
+            step[order[corner - 1]] = 1;
+        }
+        let d: [f32; 4] =
+            std::array::from_fn(|i| origin[i] - step[i] as f32 - corner as f32 * UNSKEW_4D);
+        let a = RADIUS_4D - d.iter().map(|c| c * c).sum::<f32>();
+        if a > 0.0 {
+            let hash = hash4(
+                seed,
+                cell[0].wrapping_add(step[0]),
+                cell[1].wrapping_add(step[1]),
+                cell[2].wrapping_add(step[2]),
+                cell[3].wrapping_add(step[3]),
+            );
+            value += falloff(a) * grad4(hash, d);
+        }
+    }
+    value * NORMALIZER_4D
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Noise, NoiseKind, NoiseLayer};
+
+    #[test]
+    fn simplex_layers_have_no_zeros_on_the_grid() {
+        let layer = NoiseLayer::new(1.0, 1.0);
+        let perlin = Noise::new(&[layer], 42);
+        let simplex = Noise::new(&[layer.with_kind(NoiseKind::OpenSimplex2)], 42);
+        // 펄린 노이즈는 정수 격자점에서 항상 0이 되어 격자 무늬가 드러남
+        let grid = (0..64).map(|i| ((i % 8) as f32, (i / 8) as f32));
+        assert!(grid.clone().all(|(x, y)| perlin.noise2(x, y) == 0.0));
+        assert!(grid.clone().any(|(x, y)| simplex.noise2(x, y).abs() > 0.1));
+        assert!(grid
+            .clone()
+            .any(|(x, y)| simplex.noise3(x, y, 3.0).abs() > 0.1));
+        assert!(grid
+            .clone()
+            .any(|(x, y)| simplex.noise4(x, y, 3.0, 5.0).abs() > 0.1));
+    }
+}
@@ -0,0 +1,77 @@
+//! Domain warping: moving sample coordinates by another noise before sampling, which
+//! bends straight features into meanders and smooth blobs into swirls.
+
+use crate::Noise;
+
+// 축마다 같은 노이즈의 멀리 떨어진 곳을 읽어서 서로 다른 오프셋을 얻음
+const AXIS_OFFSETS: [[f32; 4]; 4] = [
+    [0.0, 0.0, 0.0, 0.0],
+    [5_213.7, -1_349.1, 2_671.3, 0.0],
+    [-3_907.3, 4_481.9, -827.1, 0.0],
+    [1_733.3, 2_293.7, -4_139.9, 0.0],
+];
+
+#[derive(Clone)]
+pub struct Warp {
+    noise: Noise,
+    strength: f32,
+}
+
+impl Warp {
+    /// Moves coordinates by up to about `strength` times the amplitude of `noise`.
+    pub fn new(noise: Noise, strength: f32) -> Warp {
+        Warp { noise, strength }
+    }
+
+    pub fn warp2(&self, x: f32, y: f32) -> (f32, f32) {
+        let offset = |[ox, oy, ..]: [f32; 4]| self.noise.noise2(x + ox, y + oy) * self.strength;
+        (x + offset(AXIS_OFFSETS[0]), y + offset(AXIS_OFFSETS[1]))
+    }
+
+    pub fn warp3(&self, x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+        let offset =
+            |[ox, oy, oz, _]: [f32; 4]| self.noise.noise3(x + ox, y + oy, z + oz) * self.strength;
+        (
+            x + offset(AXIS_OFFSETS[0]),
+            y + offset(AXIS_OFFSETS[1]),
+            z + offset(AXIS_OFFSETS[2]),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NoiseLayer;
+
+    #[test]
+    fn warps_stay_within_their_strength() {
+        let noise = Noise::new(&[NoiseLayer::new(0.05, 1.0)], 42);
+        let warp = Warp::new(noise.clone(), 8.0);
+        let still = Warp::new(noise, 0.0);
+        let mut moved = 0.0f32;
+        for i in 0..500 {
+            let (x, y, z) = (i as f32 * 1.7, i as f32 * -2.3, i as f32 * 0.9);
+            let (wx, wy, wz) = warp.warp3(x, y, z);
+            let (fx, fy) = warp.warp2(x, y);
+            for d in [wx - x, wy - y, wz - z, fx - x, fy - y] {
+                assert!(d.abs() <= 8.0);
+                moved = moved.max(d.abs());
+            }
+            assert_eq!(still.warp3(x, y, z), (x, y, z));
+            assert_eq!(still.warp2(x, y), (x, y));
+        }
+        assert!(moved > 2.0, "barely moved: {}", moved);
+    }
+
+    #[test]
+    fn axes_are_warped_independently() {
+        let warp = Warp::new(Noise::new(&[NoiseLayer::new(0.05, 1.0)], 42), 8.0);
+        let differs = (0..100).any(|i| {
+            let (x, y) = (i as f32 * 3.1, i as f32 * 1.3);
+            let (wx, wy) = warp.warp2(x, y);
+            wx - x != wy - y
+        });
+        assert!(differs);
+    }
+}
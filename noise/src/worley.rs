@@ -0,0 +1,229 @@
+//! Cellular noise: space split into the cells of points scattered one per unit square
+//! or cube of a jittered grid.
+
+use crate::{hash2, hash3, mix};
+
+/// The distances from a sample to its nearest and second nearest feature point, in
+/// units of the grid, and an id of the cell of the nearest one.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cell {
+    pub f1: f32,
+    pub f2: f32,
+    pub id: u64,
+}
+
+impl Cell {
+    fn new() -> Cell {
+        Cell {
+            f1: f32::INFINITY,
+            f2: f32::INFINITY,
+            id: 0,
+        }
+    }
+
+    fn offer(&mut self, distance: f32, id: u64) {
+        if distance < self.f1 {
+            self.f2 = self.f1;
+            self.f1 = distance;
+            self.id = id;
+        } else if distance < self.f2 {
+            self.f2 = distance;
+        }
+    }
+
+    /// A value in [0, 1) that stays the same over the whole cell.
+    pub fn value(&self) -> f32 {
+        (self.id >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[derive(Clone)]
+pub struct Worley {
+    frequency: f32,
+    seed: u64,
+}
+
+/// Turns 21 bits of a hash, starting at `shift`, into an offset in [0, 1).
+fn jitter(hash: u64, shift: u32) -> f32 {
+    (hash >> shift & 0x1f_ffff) as f32 / (1u32 << 21) as f32
+}
+
+impl Worley {
+    pub fn new(frequency: f32, seed: u64) -> Worley {
+        Worley {
+            frequency,
+            seed: mix(seed),
+        }
+    }
+
+    pub fn worley2(&self, x: f32, y: f32) -> Cell {
+        let (x, y) = (x * self.frequency, y * self.frequency);
+        let (xi, yi) = (x.floor() as i32, y.floor() as i32);
+        let inside = [x - x.floor(), y - y.floor()];
+        let mut cell = Cell::new();
+        // 한 겹씩 넓혀가며 찾다가, 다음 겹의 칸이 모두 F2보다 멀어지면 멈춤
+        for ring in 0i32.. {
+            if ring > 1 && (ring - 1) as f32 >= cell.f2 {
+                break;
+            }
+            for j in -ring..=ring {
+                for i in -ring..=ring {
+                    if i.abs().max(j.abs()) != ring || gap(&[i, j], &inside) >= cell.f2 {
+                        continue;
+                    }
+                    let (cx, cy) = (xi.wrapping_add(i), yi.wrapping_add(j));
+                    let hash = hash2(self.seed, cx, cy);
+                    let dx = cx as f32 + jitter(hash, 0) - x;
+                    let dy = cy as f32 + jitter(hash, 21) - y;
+                    cell.offer((dx * dx + dy * dy).sqrt(), hash);
+                }
+            }
+        }
+        cell
+    }
+
+    pub fn worley3(&self, x: f32, y: f32, z: f32) -> Cell {
+        let [x, y, z] = [x, y, z].map(|c| c * self.frequency);
+        let [xi, yi, zi] = [x, y, z].map(|c| c.floor() as i32);
+        let inside = [x, y, z].map(|c| c - c.floor());
+        let mut cell = Cell::new();
+        for ring in 0i32.. {
+            if ring > 1 && (ring - 1) as f32 >= cell.f2 {
+                break;
+            }
+            for k in -ring..=ring {
+                for j in -ring..=ring {
+                    for i in -ring..=ring {
+                        let far = i.abs().max(j.abs()).max(k.abs());
+                        if far != ring || gap(&[i, j, k], &inside) >= cell.f2 {
+                            continue;
+                        }
+                        let (cx, cy) = (xi.wrapping_add(i), yi.wrapping_add(j));
+                        let cz = zi.wrapping_add(k);
+                        let hash = hash3(self.seed, cx, cy, cz);
+                        let dx = cx as f32 + jitter(hash, 0) - x;
+                        let dy = cy as f32 + jitter(hash, 21) - y;
+                        let dz = cz as f32 + jitter(hash, 42) - z;
+                        cell.offer((dx * dx + dy * dy + dz * dz).sqrt(), hash);
+                    }
+                }
+            }
+        }
+        cell
+    }
+}
+
+/// The shortest distance from a sample at `inside` of its own cell to any point of
+/// the cell `offset` cells away.
+fn gap(offset: &[i32], inside: &[f32]) -> f32 {
+    let mut total = 0.0;
+    for (&o, &f) in offset.iter().zip(inside) {
+        let d = match o {
+            0 => 0.0,
+            o if o > 0 => o as f32 - f,
+            o => f - (o + 1) as f32,
+        };
+        total += d * d;
+    }
+    total.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_ordered_and_stable() {
+        let worley = Worley::new(0.1, 42);
+        let mut ids = Vec::new();
+        for i in 0..400 {
+            let (x, y) = ((i % 20) as f32 * 3.1, (i / 20) as f32 * 2.7);
+            let flat = worley.worley2(x, y);
+            let solid = worley.worley3(x, y, 4.0);
+            for cell in [flat, solid] {
+                assert!(cell.f1 <= cell.f2);
+                assert!(cell.f1 < 1.5);
+                assert!((0.0..1.0).contains(&cell.value()));
+            }
+            assert_eq!(Worley::new(0.1, 42).worley2(x, y), flat);
+            ids.push(flat.id);
+        }
+        ids.sort();
+        ids.dedup();
+        // 격자 간격이 10이므로 60x50 영역은 여러 세포에 걸침
+        assert!(ids.len() > 4, "only {} cells", ids.len());
+        assert_ne!(
+            Worley::new(0.1, 42).worley2(3.0, 4.0),
+            Worley::new(0.1, 43).worley2(3.0, 4.0)
+        );
+    }
+
+    #[test]
+    fn nearby_samples_share_a_cell() {
+        let worley = Worley::new(1.0, 42);
+        let mut same = 0;
+        for i in 0..1000 {
+            let (x, y) = (i as f32 * 0.37, i as f32 * 0.19);
+            let a = worley.worley2(x, y);
+            let b = worley.worley2(x + 0.001, y);
+            assert!((a.f1 - b.f1).abs() <= 0.0011);
+            if a.id == b.id {
+                same += 1;
+            }
+        }
+        assert!(same > 990);
+    }
+
+    /// Every feature point within `reach` cells, sorted by distance.
+    fn brute_force(worley: &Worley, p: [f32; 3], dimensions: usize, reach: i32) -> Vec<(f32, u64)> {
+        let scaled = p.map(|c| c * worley.frequency);
+        let base = scaled.map(|c| c.floor() as i32);
+        let mut points = Vec::new();
+        let depth = if dimensions == 3 { reach } else { 0 };
+        for k in -depth..=depth {
+            for j in -reach..=reach {
+                for i in -reach..=reach {
+                    let (cx, cy, cz) = (base[0] + i, base[1] + j, base[2] + k);
+                    let (hash, d) = if dimensions == 2 {
+                        let hash = hash2(worley.seed, cx, cy);
+                        let dx = cx as f32 + jitter(hash, 0) - scaled[0];
+                        let dy = cy as f32 + jitter(hash, 21) - scaled[1];
+                        (hash, dx * dx + dy * dy)
+                    } else {
+                        let hash = hash3(worley.seed, cx, cy, cz);
+                        let dx = cx as f32 + jitter(hash, 0) - scaled[0];
+                        let dy = cy as f32 + jitter(hash, 21) - scaled[1];
+                        let dz = cz as f32 + jitter(hash, 42) - scaled[2];
+                        (hash, dx * dx + dy * dy + dz * dz)
+                    };
+                    points.push((d.sqrt(), hash));
+                }
+            }
+        }
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        points
+    }
+
+    #[test]
+    fn distances_match_a_brute_force_search() {
+        let worley = Worley::new(1.0, 42);
+        let mut state = 7u64;
+        let mut random = || {
+            state = mix(state);
+            (state >> 40) as f32 / (1u64 << 24) as f32 * 200.0 - 100.0
+        };
+        for _ in 0..20_000 {
+            let p = [random(), random(), random()];
+            for dimensions in [2, 3] {
+                let cell = if dimensions == 2 {
+                    worley.worley2(p[0], p[1])
+                } else {
+                    worley.worley3(p[0], p[1], p[2])
+                };
+                let points = brute_force(&worley, p, dimensions, 3);
+                assert_eq!((cell.f1, cell.id), points[0], "F1 at {:?}", p);
+                assert_eq!(cell.f2, points[1].0, "F2 at {:?}", p);
+            }
+        }
+    }
+}
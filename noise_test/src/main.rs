@@ -5,7 +5,7 @@ use std::{env, fs::File, io::BufWriter};
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    if args.len() < 10 || (args.len() - 8) % 2 != 0 {
+    if args.len() < 10 || !(args.len() - 8).is_multiple_of(2) {
         eprintln!("usage: noise_test <output_path> <image_size> <top_left.x> <top_left.y> <bottom_right.x> <bottom_right.y> <seed> <layer1.frequency> <layer1.amplitude> [<layer2.frequency> <layer2.amplitude> [...]]");
         return;
     }
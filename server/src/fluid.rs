@@ -0,0 +1,387 @@
+//! Fluid flow.
+//!
+//! Only cells that may change are looked at: an edit wakes the cell and its six
+//! neighbours, and every cell the simulation changes wakes its own neighbours for
+//! the next tick. Within a tick every woken cell is decided from the same snapshot
+//! and in coordinate order, so the outcome does not depend on timing or on the
+//! order the edits came in.
+//!
+//! An empty or flowing cell directly below any fluid is falling and becomes
+//! `Flowing(1)`. Otherwise it takes one level more than its lowest horizontal
+//! neighbour that rests on a floor, and dries up once no neighbour feeds it.
+//! Sources never change, and fluids only move into empty cells.
+//!
+//! Cells woken in chunks that are not loaded wait until their chunk loads, so flow
+//! picks up again where it stopped at the edge of the loaded area. Only the most
+//! recent `MAX_PARKED` of them are kept, since some of those chunks may never load.
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::sync::Arc;
+
+use map_types::{blocks, Chunk, Cube, FluidLevel, CHUNK_SIZE, MAP_HEIGHT};
+
+use crate::ChunkIndex;
+
+/// A cube in world coordinates.
+pub type BlockPos = (i32, i32, i32);
+
+/// Keeps one tick bounded; anything beyond waits for the next one.
+const MAX_UPDATES_PER_TICK: usize = 1024;
+/// Most cells kept waiting for their chunks to load. Beyond it the chunks that have
+/// waited longest are forgotten.
+const MAX_PARKED: usize = 65536;
+
+const HORIZONTAL: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+pub fn chunk_of(pos: BlockPos) -> ChunkIndex {
+    (
+        pos.0.div_euclid(CHUNK_SIZE as i32),
+        pos.1.div_euclid(CHUNK_SIZE as i32),
+    )
+}
+
+/// Position inside the chunk returned by `chunk_of`.
+pub fn local_of(pos: BlockPos) -> (usize, usize, usize) {
+    (
+        pos.0.rem_euclid(CHUNK_SIZE as i32) as usize,
+        pos.1.rem_euclid(CHUNK_SIZE as i32) as usize,
+        pos.2 as usize,
+    )
+}
+
+pub fn world_pos(index: ChunkIndex, x: usize, y: usize, z: usize) -> BlockPos {
+    (
+        index.0 * CHUNK_SIZE as i32 + x as i32,
+        index.1 * CHUNK_SIZE as i32 + y as i32,
+        z as i32,
+    )
+}
+
+/// `None` outside the map or in chunks that are not loaded; both block flow.
+fn cube_at(chunks: &HashMap<ChunkIndex, Arc<Chunk>>, pos: BlockPos) -> Option<Cube> {
+    if pos.2 < 0 || pos.2 >= MAP_HEIGHT as i32 {
+        return None;
+    }
+    let (x, y, z) = local_of(pos);
+    chunks.get(&chunk_of(pos)).map(|chunk| chunk.get(x, y, z))
+}
+
+/// Fluid above these falls instead of spreading. Sources count as a floor, so
+/// water can still spread over a lake.
+fn is_open(cube: Cube) -> bool {
+    cube == Cube::EMPTY || matches!(cube.fluid(), Some(FluidLevel::Flowing(_)))
+}
+
+fn flow_distance(level: FluidLevel) -> u8 {
+    match level {
+        FluidLevel::Source => 0,
+        FluidLevel::Flowing(level) => level,
+    }
+}
+
+#[derive(Default)]
+pub struct Fluids {
+    pending: BTreeSet<BlockPos>,
+    /// Woken cells of chunks that are not loaded, by chunk.
+    parked: HashMap<ChunkIndex, BTreeSet<BlockPos>>,
+    /// The chunks in `parked`, the one that has waited longest first.
+    parked_order: VecDeque<ChunkIndex>,
+    parked_cells: usize,
+}
+
+impl Fluids {
+    /// Schedules `pos` and its neighbours after the cube there changed.
+    pub fn wake(&mut self, pos: BlockPos) {
+        self.pending.insert(pos);
+        self.pending.insert((pos.0, pos.1, pos.2 + 1));
+        self.pending.insert((pos.0, pos.1, pos.2 - 1));
+        for (dx, dy) in HORIZONTAL {
+            self.pending.insert((pos.0 + dx, pos.1 + dy, pos.2));
+        }
+    }
+
+    /// Wakes the cells that were waiting for chunk `index` to load.
+    pub fn chunk_loaded(&mut self, index: ChunkIndex) {
+        if let Some(cells) = self.parked.remove(&index) {
+            self.parked_order.retain(|&parked| parked != index);
+            self.parked_cells -= cells.len();
+            self.pending.extend(cells);
+        }
+    }
+
+    /// Keeps a woken cell of a chunk that is not loaded until the chunk loads.
+    fn park(&mut self, pos: BlockPos) {
+        let index = chunk_of(pos);
+        if !self.parked.contains_key(&index) {
+            self.parked_order.push_back(index);
+        }
+        if self.parked.entry(index).or_default().insert(pos) {
+            self.parked_cells += 1;
+        }
+        while self.parked_cells > MAX_PARKED {
+            let Some(oldest) = self.parked_order.pop_front() else {
+                break;
+            };
+            if let Some(cells) = self.parked.remove(&oldest) {
+                self.parked_cells -= cells.len();
+            }
+        }
+    }
+
+    /// Whether there is nothing to do until something changes or a chunk loads.
+    pub fn is_idle(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Advances the flow by one step and returns every cube it changed, in
+    /// coordinate order. Changes are already applied to `chunks`.
+    pub fn tick(&mut self, chunks: &mut HashMap<ChunkIndex, Arc<Chunk>>) -> Vec<(BlockPos, Cube)> {
+        let mut woken = Vec::new();
+        while woken.len() < MAX_UPDATES_PER_TICK {
+            match self.pending.pop_first() {
+                Some(pos) => woken.push(pos),
+                None => break,
+            }
+        }
+
+        let changes: Vec<(BlockPos, Cube)> = woken
+            .into_iter()
+            .filter_map(|pos| {
+                let Some(current) = cube_at(chunks, pos) else {
+                    if (0..MAP_HEIGHT as i32).contains(&pos.2) {
+                        self.park(pos);
+                    }
+                    return None;
+                };
+                let next = next_state(chunks, pos, current)?;
+                (next != current).then_some((pos, next))
+            })
+            .collect();
+
+        for &(pos, cube) in &changes {
+            let (x, y, z) = local_of(pos);
+            if let Some(chunk) = chunks.get_mut(&chunk_of(pos)) {
+                Arc::make_mut(chunk).set(x, y, z, cube);
+            }
+            self.wake(pos);
+        }
+        changes
+    }
+}
+
+/// What the cell at `pos` should hold next, or `None` if fluids cannot affect it.
+fn next_state(
+    chunks: &HashMap<ChunkIndex, Arc<Chunk>>,
+    pos: BlockPos,
+    current: Cube,
+) -> Option<Cube> {
+    match current.fluid() {
+        Some(FluidLevel::Source) => return None,
+        Some(_) => {}
+        None if current != Cube::EMPTY => return None,
+        None => {}
+    }
+
+    let above = cube_at(chunks, (pos.0, pos.1, pos.2 + 1));
+    if above.is_some_and(|cube| cube.fluid().is_some()) {
+        return Some(blocks::water(FluidLevel::Flowing(1)));
+    }
+
+    let nearest = HORIZONTAL
+        .iter()
+        .filter_map(|(dx, dy)| {
+            let neighbor = (pos.0 + dx, pos.1 + dy, pos.2);
+            let level = cube_at(chunks, neighbor)?.fluid()?;
+            let below = cube_at(chunks, (neighbor.0, neighbor.1, neighbor.2 - 1));
+            if below.is_some_and(is_open) {
+                return None;
+            }
+            Some(flow_distance(level) + 1)
+        })
+        .min();
+    Some(match nearest {
+        Some(distance) if distance <= FluidLevel::MAX_FLOW => {
+            blocks::water(FluidLevel::Flowing(distance))
+        }
+        _ => Cube::EMPTY,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 2x1 chunk basin with a stone floor at z = 0 and nothing else.
+    fn basin() -> HashMap<ChunkIndex, Arc<Chunk>> {
+        HashMap::from([((0, 0), floor()), ((1, 0), floor())])
+    }
+
+    fn floor() -> Arc<Chunk> {
+        let mut chunk = Chunk::new();
+        for x in 0..CHUNK_SIZE {
+            for y in 0..CHUNK_SIZE {
+                chunk.set(x, y, 0, blocks::STONE);
+            }
+        }
+        Arc::new(chunk)
+    }
+
+    fn set(chunks: &mut HashMap<ChunkIndex, Arc<Chunk>>, pos: BlockPos, cube: Cube) {
+        let (x, y, z) = local_of(pos);
+        Arc::make_mut(chunks.get_mut(&chunk_of(pos)).unwrap()).set(x, y, z, cube);
+    }
+
+    fn get(chunks: &HashMap<ChunkIndex, Arc<Chunk>>, pos: BlockPos) -> Cube {
+        cube_at(chunks, pos).unwrap()
+    }
+
+    /// Ticks until nothing is pending and returns every batch of changes.
+    fn settle(
+        fluids: &mut Fluids,
+        chunks: &mut HashMap<ChunkIndex, Arc<Chunk>>,
+    ) -> Vec<Vec<(BlockPos, Cube)>> {
+        let mut batches = Vec::new();
+        while !fluids.is_idle() {
+            batches.push(fluids.tick(chunks));
+            assert!(batches.len() < 1000, "flow never settled");
+        }
+        batches
+    }
+
+    #[test]
+    fn source_spreads_with_decreasing_levels() {
+        let mut chunks = basin();
+        let mut fluids = Fluids::default();
+        set(&mut chunks, (8, 8, 1), blocks::WATER);
+        fluids.wake((8, 8, 1));
+        settle(&mut fluids, &mut chunks);
+
+        for distance in 1..=FluidLevel::MAX_FLOW as i32 {
+            assert_eq!(
+                get(&chunks, (8 + distance, 8, 1)),
+                blocks::water(FluidLevel::Flowing(distance as u8))
+            );
+        }
+        assert_eq!(get(&chunks, (8 + 8, 8, 1)), Cube::EMPTY);
+        // Spreading follows the grid, so diagonals are two steps away.
+        assert_eq!(
+            get(&chunks, (9, 9, 1)),
+            blocks::water(FluidLevel::Flowing(2))
+        );
+        assert_eq!(get(&chunks, (8, 8, 2)), Cube::EMPTY);
+    }
+
+    #[test]
+    fn water_falls_before_spreading_across_chunks() {
+        let mut chunks = basin();
+        let mut fluids = Fluids::default();
+        // Ledge near the chunk border with a source on top.
+        set(&mut chunks, (14, 4, 5), blocks::STONE);
+        set(&mut chunks, (14, 4, 6), blocks::WATER);
+        fluids.wake((14, 4, 6));
+        settle(&mut fluids, &mut chunks);
+
+        let flowing_1 = blocks::water(FluidLevel::Flowing(1));
+        let fall = (15, 4);
+        for z in 1..6 {
+            assert_eq!(get(&chunks, (fall.0, fall.1, z)), flowing_1);
+        }
+        // Spreads out at the bottom only, into the next chunk.
+        assert_eq!(
+            get(&chunks, (17, 4, 1)),
+            blocks::water(FluidLevel::Flowing(3))
+        );
+        assert_eq!(get(&chunks, (17, 4, 2)), Cube::EMPTY);
+    }
+
+    #[test]
+    fn removing_the_source_dries_the_flow() {
+        let mut chunks = basin();
+        let mut fluids = Fluids::default();
+        set(&mut chunks, (4, 4, 1), blocks::WATER);
+        fluids.wake((4, 4, 1));
+        settle(&mut fluids, &mut chunks);
+
+        set(&mut chunks, (4, 4, 1), blocks::STONE);
+        fluids.wake((4, 4, 1));
+        settle(&mut fluids, &mut chunks);
+
+        for chunk in chunks.values() {
+            for x in 0..CHUNK_SIZE {
+                for y in 0..CHUNK_SIZE {
+                    assert_eq!(chunk.get(x, y, 1).fluid(), None);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn flow_is_deterministic_and_stays_in_loaded_chunks() {
+        let run = |wake_order: &[BlockPos]| {
+            let mut chunks = basin();
+            let mut fluids = Fluids::default();
+            for &pos in wake_order {
+                set(&mut chunks, pos, blocks::WATER);
+            }
+            for &pos in wake_order {
+                fluids.wake(pos);
+            }
+            let batches = settle(&mut fluids, &mut chunks);
+            (batches, chunks)
+        };
+        let sources = [(1, 1, 1), (20, 10, 1), (0, 15, 1)];
+        let (batches, chunks) = run(&sources);
+        let (reversed_batches, _) = run(&[sources[2], sources[1], sources[0]]);
+        assert_eq!(batches, reversed_batches);
+
+        // Nothing was written outside the two loaded chunks.
+        assert_eq!(chunks.len(), 2);
+        assert!(batches
+            .iter()
+            .flatten()
+            .all(|(pos, _)| chunks.contains_key(&chunk_of(*pos))));
+    }
+
+    #[test]
+    fn flow_resumes_when_the_next_chunk_loads() {
+        let mut chunks = basin();
+        let mut fluids = Fluids::default();
+        let edge = 2 * CHUNK_SIZE as i32;
+        set(&mut chunks, (edge - 3, 4, 1), blocks::WATER);
+        fluids.wake((edge - 3, 4, 1));
+        settle(&mut fluids, &mut chunks);
+        assert_eq!(chunks.len(), 2);
+
+        chunks.insert((2, 0), floor());
+        fluids.chunk_loaded((2, 0));
+        settle(&mut fluids, &mut chunks);
+        for distance in 3..=FluidLevel::MAX_FLOW as i32 {
+            assert_eq!(
+                get(&chunks, (edge - 3 + distance, 4, 1)),
+                blocks::water(FluidLevel::Flowing(distance as u8))
+            );
+        }
+        assert!(!fluids.parked.contains_key(&(2, 0)));
+    }
+
+    #[test]
+    fn chunks_that_never_load_are_forgotten_first() {
+        let mut fluids = Fluids::default();
+        fluids.park((-1, 0, 1));
+        let mut x = CHUNK_SIZE as i32;
+        while fluids.parked.contains_key(&(-1, 0)) {
+            for z in 0..MAP_HEIGHT as i32 {
+                fluids.park((x, 0, z));
+            }
+            x += 1;
+        }
+        assert!(fluids.parked_cells <= MAX_PARKED);
+        assert_eq!(
+            fluids.parked_cells,
+            fluids.parked.values().map(BTreeSet::len).sum::<usize>()
+        );
+        // The chunk that started waiting last is still there.
+        fluids.chunk_loaded(chunk_of((x - 1, 0, 1)));
+        assert!(!fluids.is_idle());
+    }
+}
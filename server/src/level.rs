@@ -0,0 +1,131 @@
+//! World settings that are fixed when a world is created.
+//!
+//! They live in a small `key = value` text file next to the region files, so a world
+//! keeps generating the same terrain no matter how the server is started later.
+
+use std::collections::hash_map::RandomState;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+const FILE_NAME: &str = "level.txt";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Level {
+    pub seed: u64,
+}
+
+impl Level {
+    /// Reads the settings of the world in `dir`, or creates them for a new world with
+    /// `seed`, or a random seed if none is given. Asking an existing world for a
+    /// different seed is an error, since the new terrain would not match the saved one.
+    pub fn open(dir: impl AsRef<Path>, seed: Option<u64>) -> io::Result<Level> {
+        let path = dir.as_ref().join(FILE_NAME);
+        let level = match fs::read_to_string(&path) {
+            Ok(text) => parse(&text)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                fs::create_dir_all(dir.as_ref())?;
+                let level = Level {
+                    seed: seed.unwrap_or_else(random_seed),
+                };
+                fs::write(&path, format!("seed = {}\n", level.seed))?;
+                return Ok(level);
+            }
+            Err(e) => return Err(e),
+        };
+        match seed {
+            Some(seed) if seed != level.seed => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "the world was created with seed {}, not {}",
+                    level.seed, seed
+                ),
+            )),
+            _ => Ok(level),
+        }
+    }
+}
+
+fn parse(text: &str) -> io::Result<Level> {
+    let invalid = |detail: String| io::Error::new(io::ErrorKind::InvalidData, detail);
+    let mut seed = None;
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| invalid(format!("expected `key = value`, got {:?}", line)))?;
+        match key.trim() {
+            "seed" => {
+                let value = value.trim();
+                seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| invalid(format!("invalid seed {:?}", value)))?,
+                );
+            }
+            other => return Err(invalid(format!("unknown setting {:?}", other))),
+        }
+    }
+    Ok(Level {
+        seed: seed.ok_or_else(|| invalid(format!("{} has no seed", FILE_NAME)))?,
+    })
+}
+
+fn random_seed() -> u64 {
+    // RandomState는 프로세스마다 다른 키로 초기화됨
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(elapsed) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(elapsed.as_nanos());
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("level-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn seed_is_chosen_once_and_kept() {
+        let dir = temp_dir("kept");
+        let created = Level::open(&dir, None).unwrap();
+        assert_eq!(Level::open(&dir, None).unwrap(), created);
+        assert_eq!(Level::open(&dir, Some(created.seed)).unwrap(), created);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn requested_seed_only_applies_to_new_worlds() {
+        let dir = temp_dir("requested");
+        assert_eq!(Level::open(&dir, Some(1234)).unwrap().seed, 1234);
+        let e = Level::open(&dir, Some(4321)).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn new_worlds_get_their_directory() {
+        let parent = temp_dir("fresh");
+        let dir = parent.join("world");
+        assert_eq!(Level::open(&dir, Some(7)).unwrap().seed, 7);
+        assert!(dir.join(FILE_NAME).is_file());
+        fs::remove_dir_all(&parent).unwrap();
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        for text in ["", "seed = x\n", "seed 3\n", "seed = 3\ncolor = 4\n"] {
+            assert!(parse(text).is_err(), "{:?} was accepted", text);
+        }
+        assert_eq!(parse("  seed =  99 \n\n").unwrap().seed, 99);
+    }
+}
@@ -1,32 +1,65 @@
-use messages::{ClientMessage, PlayerPosition, ServerMessage};
+use map_types::{block, blocks, Cube, Dimension, CHUNK_SIZE, MAP_HEIGHT};
+use messages::codec::{self, FrameDecoder};
+use messages::{BlockChange, ClientMessage, PlayerPosition, ServerMessage, PROTOCOL_VERSION};
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{tcp::OwnedWriteHalf, TcpListener};
+use tokio::net::{
+    tcp::{OwnedReadHalf, OwnedWriteHalf},
+    TcpListener, TcpStream,
+};
 use tokio::sync::Mutex;
 
+mod fluid;
+mod level;
+mod portal;
+mod protocol;
+mod region;
+mod world;
+
+use level::Level;
+use protocol::ProtocolError;
+use world::{ChunkChanges, Worlds};
+
 type ChunkIndex = (i32, i32);
+/// A chunk of one dimension.
+type WorldChunk = (Dimension, ChunkIndex);
+const MAX_VIEW_DISTANCE: u32 = 32;
+const FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+const FLUID_TICK_INTERVAL: Duration = Duration::from_millis(250);
+
+type WatcherMap = HashMap<WorldChunk, Arc<Mutex<HashSet<u32>>>>;
 
 struct Server {
     client_map: BTreeMap<u32, Arc<Mutex<Client>>>,
-    watchers: Arc<Mutex<HashMap<ChunkIndex, Arc<Mutex<HashSet<u32>>>>>>,
+    watchers: Arc<Mutex<WatcherMap>>,
+    worlds: Arc<Worlds>,
 }
 
 struct Client {
     player_id: u32,
-    watching_chunks: Arc<Mutex<HashSet<ChunkIndex>>>,
+    player_name: String,
+    /// Where the player is; moves reported for another dimension are from before
+    /// a portal took them away.
+    dimension: Dimension,
+    /// Whether the player stood in a portal at their last move. A portal only takes
+    /// players who step into it, so they can walk out of the one they arrived in.
+    in_portal: bool,
+    watching_chunks: Arc<Mutex<HashSet<WorldChunk>>>,
     writer: Arc<Mutex<OwnedWriteHalf>>,
     buffer: Arc<Mutex<VecDeque<ServerMessage>>>,
     is_sender_spawned: Arc<Mutex<bool>>,
 }
 
 impl Server {
-    fn new() -> Self {
+    fn new(worlds: Arc<Worlds>) -> Self {
         Server {
             client_map: BTreeMap::new(),
             watchers: Arc::new(Mutex::new(HashMap::new())),
+            worlds,
         }
     }
 
@@ -35,13 +68,13 @@ impl Server {
         client: &Arc<Mutex<Client>>,
         msg: ClientMessage,
         server_arc: Arc<Mutex<Server>>,
-    ) {
+    ) -> Result<(), ProtocolError> {
         let pid = client.lock().await.player_id;
         match msg {
             ClientMessage::Move { position } => {
                 let move_msg = ServerMessage::PlayerMove {
                     moved_player_id: pid,
-                    position,
+                    position: position.clone(),
                 };
                 for (&other_pid, other_client) in &self.client_map {
                     if other_pid != pid {
@@ -52,47 +85,270 @@ impl Server {
                             .await;
                     }
                 }
+                self.step_into_portal(client, &position, server_arc).await;
             }
-            ClientMessage::WatchChunk { x, y } => {
+            ClientMessage::Hello { .. } => return Err(ProtocolError::UnexpectedHello),
+            ClientMessage::WatchChunk { dimension, x, y } => {
                 let index: ChunkIndex = (x, y);
+                let key = (dimension, index);
                 let Some(tmp) = self.client_map.get_mut(&pid) else {
-                    return;
+                    return Ok(());
                 };
                 let player = tmp.lock().await;
-                let newly_added = player.watching_chunks.lock().await.insert(index);
+                let newly_added = player.watching_chunks.lock().await.insert(key);
                 if newly_added {
                     let mut watcher = self.watchers.lock().await;
                     let entry = watcher
-                        .entry(index)
+                        .entry(key)
                         .or_insert_with(|| Arc::new(Mutex::new(HashSet::new())));
                     let mut set = entry.lock().await;
                     set.insert(pid);
                 }
+                // Always (re)send, so a client that lost its copy can ask again.
+                let world = self.worlds.get(dimension).clone();
+                let client = tmp.clone();
+                tokio::spawn(async move {
+                    let loader = world.clone();
+                    let Ok(chunk) =
+                        tokio::task::spawn_blocking(move || loader.load_chunk(index)).await
+                    else {
+                        return;
+                    };
+                    let mut client = client.lock().await;
+                    if !client.watching_chunks.lock().await.contains(&key) {
+                        return;
+                    }
+                    // Snapshot under the client lock so block edits broadcast
+                    // after this point are queued behind the chunk itself.
+                    let chunk = world.loaded_chunk(index).unwrap_or(chunk);
+                    let chunk_msg = ServerMessage::Chunk {
+                        dimension,
+                        x,
+                        y,
+                        chunk: Box::new((*chunk).clone()),
+                    };
+                    client.send(chunk_msg, server_arc).await;
+                });
             }
-            ClientMessage::UnwatchChunk { x, y } => {
-                let index: ChunkIndex = (x, y);
+            ClientMessage::UnwatchChunk { dimension, x, y } => {
+                let key = (dimension, (x, y));
                 let Some(tmp) = self.client_map.get_mut(&pid) else {
-                    return;
+                    return Ok(());
                 };
                 let player = tmp.lock().await;
-                let deleted = player.watching_chunks.lock().await.remove(&index);
+                let deleted = player.watching_chunks.lock().await.remove(&key);
                 if deleted {
                     let mut watcher = self.watchers.lock().await;
-                    let to_delete = {
-                        let set_arc = watcher.get(&index).unwrap();
-                        let mut set = set_arc.lock().await;
-                        set.remove(&pid);
-                        set.is_empty()
+                    let to_delete = match watcher.get(&key) {
+                        Some(set_arc) => {
+                            let mut set = set_arc.lock().await;
+                            set.remove(&pid);
+                            set.is_empty()
+                        }
+                        None => false,
                     };
                     if to_delete {
-                        watcher.remove(&index);
+                        watcher.remove(&key);
+                        self.worlds.get(dimension).unload_chunk(key.1);
                     }
                 }
             }
-            _ => {
-                // TODO: remove _
+            ClientMessage::DestroyBlock {
+                dimension,
+                chunk_x,
+                chunk_y,
+                block_x,
+                block_y,
+                block_z,
+            } => {
+                let (x, y, z) = validate_block_position(block_x, block_y, block_z)?;
+                let index = (chunk_x, chunk_y);
+                let world = self.worlds.get(dimension).clone();
+                // Air and unbreakable cubes such as bedrock are left alone, and so are
+                // chunks nobody has loaded.
+                let breakable = world
+                    .loaded_chunk(index)
+                    .is_some_and(|chunk| chunk.get(x, y, z).is_breakable());
+                if !breakable {
+                    return Ok(());
+                }
+                let previous = world.set_cube(index, x, y, z, Cube::EMPTY);
+                if previous != Cube::EMPTY {
+                    let destroy_msg = ServerMessage::DestroyBlock {
+                        dimension,
+                        chunk_x,
+                        chunk_y,
+                        block_x,
+                        block_y,
+                        block_z,
+                    };
+                    self.broadcast_to_watchers((dimension, index), destroy_msg, server_arc.clone())
+                        .await;
+                }
+                if previous == blocks::OBSIDIAN {
+                    let pos = fluid::world_pos(index, x, y, z);
+                    let changes = world.set_cubes(&portal::extinguish(&world, pos));
+                    self.broadcast_changes(dimension, changes, server_arc).await;
+                }
             }
+            ClientMessage::PutBlock {
+                dimension,
+                chunk_x,
+                chunk_y,
+                block_x,
+                block_y,
+                block_z,
+                cube,
+            } => {
+                let (x, y, z) = validate_block_position(block_x, block_y, block_z)?;
+                if !cube.is_defined() {
+                    return Err(ProtocolError::UnknownBlock { id: cube.0 });
+                }
+                let index = (chunk_x, chunk_y);
+                let world = self.worlds.get(dimension).clone();
+                // Only empty space and fluids can be built into; anything else needs a
+                // DestroyBlock first.
+                let replaceable = world.loaded_chunk(index).is_some_and(|chunk| {
+                    let current = chunk.get(x, y, z);
+                    current == Cube::EMPTY || current.fluid().is_some()
+                });
+                if replaceable {
+                    world.set_cube(index, x, y, z, cube);
+                    let put_msg = ServerMessage::PutBlock {
+                        dimension,
+                        chunk_x,
+                        chunk_y,
+                        block_x,
+                        block_y,
+                        block_z,
+                        cube,
+                    };
+                    self.broadcast_to_watchers((dimension, index), put_msg, server_arc.clone())
+                        .await;
+                    if cube == blocks::OBSIDIAN {
+                        let pos = fluid::world_pos(index, x, y, z);
+                        let changes = world.set_cubes(&portal::light(&world, pos));
+                        self.broadcast_changes(dimension, changes, server_arc).await;
+                    }
+                }
+            }
+        };
+        Ok(())
+    }
+
+    /// Sends the player to the other dimension if they just stepped into a portal.
+    async fn step_into_portal(
+        &self,
+        client: &Arc<Mutex<Client>>,
+        position: &PlayerPosition,
+        server_arc: Arc<Mutex<Server>>,
+    ) {
+        let &PlayerPosition::InWorld {
+            dimension,
+            position: [x, y, z],
+            horizontal_rotation,
+            vertical_rotation,
+        } = position
+        else {
+            return;
+        };
+        let mut c = client.lock().await;
+        if c.dimension != dimension {
+            return;
+        }
+        let feet = (x.floor() as i32, y.floor() as i32, z.floor() as i32);
+        let world = self.worlds.get(dimension);
+        let in_portal = [feet, (feet.0, feet.1, feet.2 + 1)]
+            .into_iter()
+            .any(|pos| world.loaded_cube_at(pos) == Some(blocks::NETHER_PORTAL));
+        let entered = in_portal && !c.in_portal;
+        c.in_portal = in_portal;
+        if !entered {
+            return;
+        }
+
+        // 도착하는 곳도 포탈 안이므로 바로 되돌아가지 않음
+        let (dimension, target) = portal::destination(dimension, feet);
+        c.dimension = dimension;
+        drop(c);
+        let world = self.worlds.get(dimension).clone();
+        let client = client.clone();
+        tokio::spawn(async move {
+            let Ok((arrival, changes)) = tokio::task::spawn_blocking(move || {
+                let (arrival, cubes) = portal::arrival(&world, dimension, target);
+                (arrival, world.set_cubes(&cubes))
+            })
+            .await
+            else {
+                return;
+            };
+            server_arc
+                .lock()
+                .await
+                .broadcast_changes(dimension, changes, server_arc.clone())
+                .await;
+            let teleport = ServerMessage::Teleport {
+                position: PlayerPosition::InWorld {
+                    dimension,
+                    position: [
+                        arrival.0 as f32 + 0.5,
+                        arrival.1 as f32 + 0.5,
+                        arrival.2 as f32,
+                    ],
+                    horizontal_rotation,
+                    vertical_rotation,
+                },
+            };
+            client.lock().await.send(teleport, server_arc).await;
+        });
+    }
+
+    /// Sends each watcher one message per chunk with the cubes that changed there.
+    async fn broadcast_changes(
+        &self,
+        dimension: Dimension,
+        changes: ChunkChanges,
+        server_arc: Arc<Mutex<Server>>,
+    ) {
+        for ((chunk_x, chunk_y), changes) in changes {
+            let changes = changes
+                .into_iter()
+                .map(|(x, y, z, cube)| BlockChange {
+                    block_x: x as u32,
+                    block_y: y as u32,
+                    block_z: z as u32,
+                    cube,
+                })
+                .collect();
+            let msg = ServerMessage::BlockChanges {
+                dimension,
+                chunk_x,
+                chunk_y,
+                changes,
+            };
+            self.broadcast_to_watchers((dimension, (chunk_x, chunk_y)), msg, server_arc.clone())
+                .await;
+        }
+    }
+
+    async fn broadcast_to_watchers(
+        &self,
+        key: WorldChunk,
+        msg: ServerMessage,
+        server_arc: Arc<Mutex<Server>>,
+    ) {
+        let Some(set_arc) = self.watchers.lock().await.get(&key).cloned() else {
+            return;
         };
+        for pid in set_arc.lock().await.iter() {
+            if let Some(client) = self.client_map.get(pid) {
+                client
+                    .lock()
+                    .await
+                    .send(msg.clone(), server_arc.clone())
+                    .await;
+            }
+        }
     }
 
     async fn add_client(&mut self, c: Arc<Mutex<Client>>) {
@@ -101,27 +357,36 @@ impl Server {
     }
 
     async fn remove_client(&mut self, player_id: u32) {
-        let tmp = self.client_map.remove(&player_id).unwrap();
+        // The sender task and the reader task may both notice a dead connection.
+        let Some(tmp) = self.client_map.remove(&player_id) else {
+            return;
+        };
         let client = tmp.lock().await;
         let mut watchers = self.watchers.lock().await;
-        for index in client.watching_chunks.lock().await.iter() {
-            let to_delete = {
-                let tmp = watchers.get(index).unwrap();
-                let mut node = tmp.lock().await;
-                node.remove(&client.player_id);
-                node.len() == 0
+        for &(dimension, index) in client.watching_chunks.lock().await.iter() {
+            let to_delete = match watchers.get(&(dimension, index)) {
+                Some(tmp) => {
+                    let mut node = tmp.lock().await;
+                    node.remove(&client.player_id);
+                    node.is_empty()
+                }
+                None => false,
             };
             if to_delete {
-                watchers.remove(index);
+                watchers.remove(&(dimension, index));
+                self.worlds.get(dimension).unload_chunk(index);
             }
         }
     }
 }
 
 impl Client {
-    fn new(player_id: u32, writer: OwnedWriteHalf) -> Self {
+    fn new(player_id: u32, hello: Hello, writer: OwnedWriteHalf) -> Self {
         Client {
             player_id,
+            player_name: hello.player_name,
+            dimension: Dimension::Overworld,
+            in_portal: false,
             watching_chunks: Arc::new(Mutex::new(HashSet::new())),
             writer: Arc::new(Mutex::new(writer)),
             buffer: Arc::new(Mutex::new(VecDeque::new())),
@@ -140,7 +405,13 @@ impl Client {
             let my_pid = self.player_id;
             tokio::spawn(async move {
                 while let Some(m) = buffer_arc.lock().await.pop_front() {
-                    let bytes = bincode::serialize(&m).unwrap();
+                    let bytes = match codec::encode(&m) {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            eprintln!("Dropping message for player {}: {}", my_pid, e);
+                            continue;
+                        }
+                    };
                     if writer_arc.lock().await.write_all(&bytes).await.is_err() {
                         server_arc.lock().await.remove_client(my_pid).await;
                         break;
@@ -155,17 +426,120 @@ impl Client {
 #[tokio::main]
 async fn main() {
     let port = env::args().nth(1).unwrap_or_else(|| "4242".to_string());
+    let world_dir = env::args().nth(2).unwrap_or_else(|| "world".to_string());
+    let seed = env::args().nth(3).map(|seed| {
+        seed.parse::<u64>().unwrap_or_else(|_| {
+            eprintln!("Invalid seed {:?}: expected a number", seed);
+            std::process::exit(1)
+        })
+    });
+    if let Ok(path) = env::var(block::DEFINITIONS_ENV) {
+        if let Err(e) = block::load_definitions(&path) {
+            eprintln!("{}: {}", path, e);
+            std::process::exit(1)
+        }
+    }
     let listener = TcpListener::bind(("0.0.0.0", port.parse::<u16>().unwrap()))
         .await
         .unwrap();
 
-    println!("Server running on port {} ...", port);
+    let level = Level::open(&world_dir, seed).unwrap_or_else(|e| {
+        eprintln!("Failed to open world {}: {}", world_dir, e);
+        std::process::exit(1)
+    });
+    let worlds = Worlds::open(&world_dir, level.seed).unwrap_or_else(|e| {
+        eprintln!("Failed to open world directory {}: {}", world_dir, e);
+        std::process::exit(1)
+    });
+    let worlds = Arc::new(worlds);
+
+    println!(
+        "Server running on port {} (world: {}, seed: {}) ...",
+        port, world_dir, level.seed
+    );
+
+    let mut flush_timer = tokio::time::interval(FLUSH_INTERVAL);
+    flush_timer.tick().await;
+    let mut fluid_timer = tokio::time::interval(FLUID_TICK_INTERVAL);
+    let server_arc = Arc::new(Mutex::new(Server::new(worlds.clone())));
+    let server = serve(listener, server_arc.clone());
+    let shutdown = shutdown_signal();
+    tokio::pin!(server, shutdown);
+    loop {
+        tokio::select! {
+            _ = &mut server => break,
+            _ = flush_timer.tick() => flush(&worlds).await,
+            _ = fluid_timer.tick() => tick_fluids(&worlds, &server_arc).await,
+            _ = &mut shutdown => {
+                println!("Shutting down ...");
+                break;
+            }
+        }
+    }
+    flush(&worlds).await;
+}
+
+/// Resolves on Ctrl-C, or on SIGTERM so `kill` also saves the world.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut terminate = signal(SignalKind::terminate()).unwrap();
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Runs the flow off the async threads and without the server lock, which is only
+/// taken to send out what changed.
+async fn tick_fluids(worlds: &Arc<Worlds>, server_arc: &Arc<Mutex<Server>>) {
+    for (dimension, world) in worlds.iter() {
+        let world = world.clone();
+        let changes = match tokio::task::spawn_blocking(move || world.tick_fluids()).await {
+            Ok(changes) => changes,
+            Err(e) => {
+                eprintln!("Fluid task failed: {}", e);
+                continue;
+            }
+        };
+        if changes.is_empty() {
+            continue;
+        }
+        server_arc
+            .lock()
+            .await
+            .broadcast_changes(dimension, changes, server_arc.clone())
+            .await;
+    }
+}
 
-    let server_arc = Arc::new(Mutex::new(Server::new()));
+async fn flush(worlds: &Arc<Worlds>) {
+    for (dimension, world) in worlds.iter() {
+        let world = world.clone();
+        match tokio::task::spawn_blocking(move || world.flush()).await {
+            Ok(Ok(0)) => {}
+            Ok(Ok(count)) => println!("Saved {} chunks of the {}", count, dimension.name()),
+            Ok(Err(e)) => eprintln!("Failed to save the {}: {}", dimension.name(), e),
+            Err(e) => eprintln!("World save task failed: {}", e),
+        }
+    }
+}
+
+async fn serve(listener: TcpListener, server_arc: Arc<Mutex<Server>>) {
     let mut last_pid = 0;
 
     loop {
-        let (socket, _) = listener.accept().await.unwrap();
+        let socket = match listener.accept().await {
+            Ok((socket, _)) => socket,
+            Err(e) => {
+                eprintln!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
         let pid = last_pid;
         last_pid += 1;
 
@@ -176,34 +550,122 @@ async fn main() {
     }
 }
 
-async fn handle_client(socket: tokio::net::TcpStream, pid: u32, server_arc: Arc<Mutex<Server>>) {
-    let (mut reader, writer) = socket.into_split();
-    let client = Arc::new(Mutex::new(Client::new(pid, writer)));
+fn validate_block_position(x: u32, y: u32, z: u32) -> Result<(usize, usize, usize), ProtocolError> {
+    if (x as usize) < CHUNK_SIZE && (y as usize) < CHUNK_SIZE && (z as usize) < MAP_HEIGHT {
+        Ok((x as usize, y as usize, z as usize))
+    } else {
+        Err(ProtocolError::InvalidBlockPosition { x, y, z })
+    }
+}
 
-    {
+struct Hello {
+    player_name: String,
+    view_distance: u32,
+}
+
+/// Waits for the client's `Hello`. `Ok(None)` means the client left before sending one.
+async fn read_hello(
+    reader: &mut OwnedReadHalf,
+    decoder: &mut FrameDecoder,
+) -> Result<Option<Hello>, ProtocolError> {
+    loop {
+        // Variant index and version come first in every build's Hello,
+        // so check them before the rest of the message has to make sense.
+        if let Ok(Some((0u32, protocol_version))) = decoder.peek::<(u32, u32)>() {
+            if protocol_version != PROTOCOL_VERSION {
+                return Err(ProtocolError::IncompatibleProtocol {
+                    client_version: protocol_version,
+                });
+            }
+        }
+        match decoder.decode::<ClientMessage>()? {
+            Some(ClientMessage::Hello {
+                player_name,
+                requested_view_distance,
+                blocks_fingerprint,
+                ..
+            }) => {
+                if blocks_fingerprint != block::registry().fingerprint() {
+                    return Err(ProtocolError::BlockDefinitionsMismatch);
+                }
+                return Ok(Some(Hello {
+                    player_name,
+                    view_distance: requested_view_distance.clamp(1, MAX_VIEW_DISTANCE),
+                }));
+            }
+            Some(_) => return Err(ProtocolError::ExpectedHello),
+            None => {}
+        }
+        match reader.read_buf(decoder.buffer_mut()).await {
+            Ok(0) | Err(_) => return Ok(None),
+            Ok(_) => {}
+        }
+    }
+}
+
+/// Feeds every complete frame to the server until the client hangs up.
+async fn read_messages(
+    reader: &mut OwnedReadHalf,
+    decoder: &mut FrameDecoder,
+    client: &Arc<Mutex<Client>>,
+    server_arc: &Arc<Mutex<Server>>,
+) -> Result<(), ProtocolError> {
+    loop {
+        while let Some(msg) = decoder.decode::<ClientMessage>()? {
+            let mut s = server_arc.lock().await;
+            s.handle_message(client, msg, server_arc.clone()).await?;
+        }
+        match reader.read_buf(decoder.buffer_mut()).await {
+            Ok(0) | Err(_) => return Ok(()),
+            Ok(_) => {}
+        }
+    }
+}
+
+async fn handle_client(socket: TcpStream, pid: u32, server_arc: Arc<Mutex<Server>>) {
+    let (mut reader, mut writer) = socket.into_split();
+    let mut decoder = FrameDecoder::new();
+
+    let hello = match read_hello(&mut reader, &mut decoder).await {
+        Ok(Some(hello)) => hello,
+        Ok(None) => return,
+        Err(e) => {
+            println!("Player {} rejected: {}", pid, e);
+            let reason = e.reason();
+            if let Ok(bytes) = codec::encode(&ServerMessage::Disconnect { reason }) {
+                let _ = writer.write_all(&bytes).await;
+            }
+            return;
+        }
+    };
+    let view_distance = hello.view_distance;
+    let client = Arc::new(Mutex::new(Client::new(pid, hello, writer)));
+
+    let seed = {
         let mut s = server_arc.lock().await;
         s.add_client(client.clone()).await;
-    }
+        s.worlds.seed()
+    };
     {
         let mut c = client.lock().await;
         let init_msg = ServerMessage::Init {
             your_player_id: pid,
             your_position: PlayerPosition::NotInWorld,
+            view_distance,
+            seed,
         };
         c.send(init_msg, server_arc.clone()).await;
+        println!("Player {} ({}) connected", pid, c.player_name);
     }
-    println!("Player {} connected", pid);
 
-    let mut buf = Vec::new();
-    while let Ok(n) = reader.read_buf(&mut buf).await {
-        if n == 0 {
-            break;
-        }
-        while let Ok((msg, consumed)) = try_deser::<ClientMessage>(&buf) {
-            buf.drain(..consumed);
-            let mut s = server_arc.lock().await;
-            s.handle_message(&client, msg, server_arc.clone()).await;
-        }
+    if let Err(e) = read_messages(&mut reader, &mut decoder, &client, &server_arc).await {
+        println!("Player {} kicked: {}", pid, e);
+        let reason = e.reason();
+        client
+            .lock()
+            .await
+            .send(ServerMessage::Disconnect { reason }, server_arc.clone())
+            .await;
     }
     {
         let mut s = server_arc.lock().await;
@@ -212,24 +674,253 @@ async fn handle_client(socket: tokio::net::TcpStream, pid: u32, server_arc: Arc<
     println!("Player {} disconnected", pid);
 }
 
-fn try_deser<T: serde::de::DeserializeOwned>(buf: &[u8]) -> Result<(T, usize), bincode::Error> {
-    let mut cur = std::io::Cursor::new(buf);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messages::DisconnectReason;
+    use std::fs;
+    use std::path::{Path, PathBuf};
 
-    match bincode::deserialize_from(&mut cur) {
-        Ok(m) => Ok((m, cur.position() as usize)),
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("server-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
 
-        Err(e) => {
-            match *e {
-                // Incomplete input is okay; return the error as-is
-                bincode::ErrorKind::Io(ref io_err)
-                    if io_err.kind() == std::io::ErrorKind::UnexpectedEof =>
-                {
-                    Err(e)
-                }
+    async fn start_server(dir: &Path) -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let worlds = Arc::new(Worlds::open(dir, 42).unwrap());
+        tokio::spawn(serve(listener, Arc::new(Mutex::new(Server::new(worlds)))));
+        addr
+    }
+
+    async fn send<T: serde::Serialize>(stream: &mut TcpStream, msg: &T) {
+        stream
+            .write_all(&codec::encode(msg).unwrap())
+            .await
+            .unwrap();
+    }
+
+    async fn recv(stream: &mut TcpStream, decoder: &mut FrameDecoder) -> Option<ServerMessage> {
+        loop {
+            if let Some(msg) = decoder.decode().unwrap() {
+                return Some(msg);
+            }
+            if stream.read_buf(decoder.buffer_mut()).await.unwrap() == 0 {
+                return None;
+            }
+        }
+    }
+
+    fn hello(protocol_version: u32) -> ClientMessage {
+        ClientMessage::Hello {
+            protocol_version,
+            player_name: "tester".to_string(),
+            requested_view_distance: 8,
+            blocks_fingerprint: block::registry().fingerprint(),
+        }
+    }
+
+    async fn join(addr: std::net::SocketAddr) -> (TcpStream, FrameDecoder) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let mut decoder = FrameDecoder::new();
+        send(&mut stream, &hello(PROTOCOL_VERSION)).await;
+        assert!(matches!(
+            recv(&mut stream, &mut decoder).await,
+            Some(ServerMessage::Init { seed: 42, .. })
+        ));
+        (stream, decoder)
+    }
+
+    #[tokio::test]
+    async fn garbage_before_hello_is_rejected() {
+        let dir = temp_dir("garbage");
+        let addr = start_server(&dir).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let mut decoder = FrameDecoder::new();
+        stream
+            .write_all(&[8, 0, 0, 0, 0xde, 0xad, 0xbe, 0xef, 0xde, 0xad, 0xbe, 0xef])
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            recv(&mut stream, &mut decoder).await,
+            Some(ServerMessage::Disconnect {
+                reason: DisconnectReason::InvalidData { .. }
+            })
+        ));
+        assert!(recv(&mut stream, &mut decoder).await.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn mismatched_version_is_rejected() {
+        let dir = temp_dir("version");
+        let addr = start_server(&dir).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let mut decoder = FrameDecoder::new();
+        send(&mut stream, &hello(PROTOCOL_VERSION + 1)).await;
+
+        match recv(&mut stream, &mut decoder).await {
+            Some(ServerMessage::Disconnect {
+                reason:
+                    DisconnectReason::IncompatibleProtocol {
+                        server_version,
+                        client_version,
+                    },
+            }) => {
+                assert_eq!(server_version, PROTOCOL_VERSION);
+                assert_eq!(client_version, PROTOCOL_VERSION + 1);
+            }
+            other => panic!(
+                "expected an incompatible protocol rejection, got {:?}",
+                other
+            ),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn different_block_definitions_are_rejected() {
+        let dir = temp_dir("blocks");
+        let addr = start_server(&dir).await;
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let mut decoder = FrameDecoder::new();
+        let mut modded = hello(PROTOCOL_VERSION);
+        if let ClientMessage::Hello {
+            blocks_fingerprint, ..
+        } = &mut modded
+        {
+            *blocks_fingerprint ^= 1;
+        }
+        send(&mut stream, &modded).await;
+
+        assert!(matches!(
+            recv(&mut stream, &mut decoder).await,
+            Some(ServerMessage::Disconnect {
+                reason: DisconnectReason::BlockDefinitionsMismatch
+            })
+        ));
 
-                // Any other error means invalid data; panic!
-                _ => panic!("Invalid data during deserialization: {:?}", e),
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn garbage_after_hello_only_drops_that_client() {
+        let dir = temp_dir("drop");
+        let addr = start_server(&dir).await;
+        let (mut watcher, mut watcher_decoder) = join(addr).await;
+
+        let (mut bad, mut bad_decoder) = join(addr).await;
+        bad.write_all(&u32::MAX.to_le_bytes()).await.unwrap();
+        assert!(matches!(
+            recv(&mut bad, &mut bad_decoder).await,
+            Some(ServerMessage::Disconnect {
+                reason: DisconnectReason::InvalidData { .. }
+            })
+        ));
+        assert!(recv(&mut bad, &mut bad_decoder).await.is_none());
+
+        let (mut mover, _) = join(addr).await;
+        send(
+            &mut mover,
+            &ClientMessage::Move {
+                position: PlayerPosition::NotInWorld,
+            },
+        )
+        .await;
+        assert!(matches!(
+            recv(&mut watcher, &mut watcher_decoder).await,
+            Some(ServerMessage::PlayerMove { .. })
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn portals_lead_to_the_nether() {
+        let dir = temp_dir("portal");
+        let addr = start_server(&dir).await;
+        let (mut stream, mut decoder) = join(addr).await;
+        let overworld = Dimension::Overworld;
+        send(
+            &mut stream,
+            &ClientMessage::WatchChunk {
+                dimension: overworld,
+                x: 0,
+                y: 0,
+            },
+        )
+        .await;
+        assert!(matches!(
+            recv(&mut stream, &mut decoder).await,
+            Some(ServerMessage::Chunk { .. })
+        ));
+
+        // 하늘 높이 4x5 흑요석 틀을 세움
+        for x in 4..8u32 {
+            for z in 240..245u32 {
+                if (5..7).contains(&x) && (241..244).contains(&z) {
+                    continue;
+                }
+                let put = ClientMessage::PutBlock {
+                    dimension: overworld,
+                    chunk_x: 0,
+                    chunk_y: 0,
+                    block_x: x,
+                    block_y: 8,
+                    block_z: z,
+                    cube: blocks::OBSIDIAN,
+                };
+                send(&mut stream, &put).await;
             }
         }
+        let into_portal = PlayerPosition::InWorld {
+            dimension: overworld,
+            position: [5.5, 8.5, 241.0],
+            horizontal_rotation: 0.0,
+            vertical_rotation: 0.0,
+        };
+        send(
+            &mut stream,
+            &ClientMessage::Move {
+                position: into_portal,
+            },
+        )
+        .await;
+        loop {
+            match recv(&mut stream, &mut decoder).await {
+                Some(ServerMessage::Teleport {
+                    position: PlayerPosition::InWorld { dimension, .. },
+                }) => {
+                    assert_eq!(dimension, Dimension::Nether);
+                    break;
+                }
+                Some(ServerMessage::PutBlock { .. } | ServerMessage::BlockChanges { .. }) => {}
+                other => panic!("expected a teleport, got {:?}", other),
+            }
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn second_hello_is_a_protocol_error() {
+        let dir = temp_dir("hello");
+        let addr = start_server(&dir).await;
+        let (mut stream, mut decoder) = join(addr).await;
+        send(&mut stream, &hello(PROTOCOL_VERSION)).await;
+
+        assert!(matches!(
+            recv(&mut stream, &mut decoder).await,
+            Some(ServerMessage::Disconnect {
+                reason: DisconnectReason::InvalidData { .. }
+            })
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
     }
 }
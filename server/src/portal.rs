@@ -0,0 +1,376 @@
+//! Nether portals.
+//!
+//! Closing a rectangular obsidian frame fills it with portal cubes. A player who
+//! steps into one is sent to the matching position of the other dimension, where
+//! distances are scaled by `SCALE`, and arrives in the nearest portal there, or in
+//! a new one built for them.
+
+use map_core::{nether, WATER_LEVEL};
+use map_types::{blocks, Cube, Dimension, CHUNK_SIZE, MAP_HEIGHT};
+
+use crate::fluid::{self, BlockPos};
+use crate::world::World;
+
+/// Smallest inside of a frame, along the frame and upwards.
+const MIN_WIDTH: i32 = 2;
+const MIN_HEIGHT: i32 = 3;
+/// Largest inside of a frame in either direction.
+const MAX_SIZE: i32 = 21;
+/// One block in the Nether is this many in the overworld.
+const SCALE: i32 = 8;
+/// How far from the destination an existing portal is still used.
+const SEARCH_RADIUS: i32 = 16;
+/// A fully portal-filled frame holds at most this many cubes.
+const MAX_PORTAL_CUBES: usize = (MAX_SIZE * MAX_SIZE) as usize;
+
+/// Directions a frame can run along; frames always stand upright.
+const AXES: [(i32, i32); 2] = [(1, 0), (0, 1)];
+
+fn offset(pos: BlockPos, (dx, dy): (i32, i32), along: i32, up: i32) -> BlockPos {
+    (pos.0 + dx * along, pos.1 + dy * along, pos.2 + up)
+}
+
+/// Portal cubes for the inside of a frame that `placed` just closed, or nothing if
+/// no empty frame is complete around it.
+pub fn light(world: &World, placed: BlockPos) -> Vec<(BlockPos, Cube)> {
+    for axis in AXES {
+        for (along, up) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            if let Some(inside) = frame_inside(world, offset(placed, axis, along, up), axis) {
+                return inside
+                    .into_iter()
+                    .map(|pos| (pos, blocks::NETHER_PORTAL))
+                    .collect();
+            }
+        }
+    }
+    Vec::new()
+}
+
+/// The empty inside of the obsidian frame along `axis` that contains `start`.
+/// Cubes in chunks that are not loaded count as neither, so the search never loads
+/// or generates anything.
+fn frame_inside(world: &World, start: BlockPos, axis: (i32, i32)) -> Option<Vec<BlockPos>> {
+    let is = |pos: BlockPos, cube: Cube| world.loaded_cube_at(pos) == Some(cube);
+    if !is(start, Cube::EMPTY) {
+        return None;
+    }
+
+    // 안쪽의 왼쪽 아래 모서리를 찾음
+    let mut corner = start;
+    for _ in 0..MAX_SIZE {
+        if !is(offset(corner, axis, 0, -1), Cube::EMPTY) {
+            break;
+        }
+        corner = offset(corner, axis, 0, -1);
+    }
+    for _ in 0..MAX_SIZE {
+        let left = offset(corner, axis, -1, 0);
+        if !is(left, Cube::EMPTY) || !is(offset(left, axis, 0, -1), blocks::OBSIDIAN) {
+            break;
+        }
+        corner = left;
+    }
+    if !is(offset(corner, axis, -1, 0), blocks::OBSIDIAN) {
+        return None;
+    }
+
+    let width = (0..MAX_SIZE)
+        .take_while(|&i| {
+            is(offset(corner, axis, i, 0), Cube::EMPTY)
+                && is(offset(corner, axis, i, -1), blocks::OBSIDIAN)
+        })
+        .count() as i32;
+    if width < MIN_WIDTH || !is(offset(corner, axis, width, 0), blocks::OBSIDIAN) {
+        return None;
+    }
+
+    let mut inside = Vec::new();
+    for height in 0..=MAX_SIZE {
+        let row = |i| offset(corner, axis, i, height);
+        if (0..width).all(|i| is(row(i), blocks::OBSIDIAN)) {
+            return (height >= MIN_HEIGHT).then_some(inside);
+        }
+        if height == MAX_SIZE
+            || !is(row(-1), blocks::OBSIDIAN)
+            || !is(row(width), blocks::OBSIDIAN)
+            || !(0..width).all(|i| is(row(i), Cube::EMPTY))
+        {
+            return None;
+        }
+        inside.extend((0..width).map(row));
+    }
+    None
+}
+
+/// Portal cubes touching `pos`, and every portal cube connected to them, turned back
+/// into air. Used when a frame is broken. Only looks at loaded chunks.
+pub fn extinguish(world: &World, pos: BlockPos) -> Vec<(BlockPos, Cube)> {
+    let mut found = Vec::new();
+    let mut pending = neighbours(pos).to_vec();
+    while let Some(pos) = pending.pop() {
+        if found.len() >= MAX_PORTAL_CUBES || found.contains(&pos) {
+            continue;
+        }
+        if world.loaded_cube_at(pos) == Some(blocks::NETHER_PORTAL) {
+            found.push(pos);
+            pending.extend(neighbours(pos));
+        }
+    }
+    found.into_iter().map(|pos| (pos, Cube::EMPTY)).collect()
+}
+
+fn neighbours(pos: BlockPos) -> [BlockPos; 6] {
+    [
+        (pos.0 + 1, pos.1, pos.2),
+        (pos.0 - 1, pos.1, pos.2),
+        (pos.0, pos.1 + 1, pos.2),
+        (pos.0, pos.1 - 1, pos.2),
+        (pos.0, pos.1, pos.2 + 1),
+        (pos.0, pos.1, pos.2 - 1),
+    ]
+}
+
+/// Where a portal at `pos` leads.
+pub fn destination(from: Dimension, pos: BlockPos) -> (Dimension, BlockPos) {
+    match from {
+        Dimension::Overworld => (
+            Dimension::Nether,
+            (pos.0.div_euclid(SCALE), pos.1.div_euclid(SCALE), pos.2),
+        ),
+        Dimension::Nether => (Dimension::Overworld, (pos.0 * SCALE, pos.1 * SCALE, pos.2)),
+    }
+}
+
+/// Heights a portal may stand at in a dimension.
+fn heights(dimension: Dimension) -> std::ops::Range<i32> {
+    match dimension {
+        Dimension::Overworld => 1..MAP_HEIGHT as i32 - 4,
+        Dimension::Nether => nether::LAVA_LEVEL as i32 + 1..nether::CEILING as i32 - 4,
+    }
+}
+
+/// The portal cube a traveller heading for `target` arrives in, and the cubes that
+/// have to change first if no portal is close enough and one has to be built.
+pub fn arrival(
+    world: &World,
+    dimension: Dimension,
+    target: BlockPos,
+) -> (BlockPos, Vec<(BlockPos, Cube)>) {
+    match find(world, dimension, target) {
+        Some(pos) => (pos, Vec::new()),
+        None => build(world, dimension, target),
+    }
+}
+
+/// The bottom cube of the portal nearest to `target`, if one is within `SEARCH_RADIUS`.
+fn find(world: &World, dimension: Dimension, target: BlockPos) -> Option<BlockPos> {
+    let size = CHUNK_SIZE as i32;
+    let chunks = |center: i32| {
+        (center - SEARCH_RADIUS).div_euclid(size)..=(center + SEARCH_RADIUS).div_euclid(size)
+    };
+    let mut nearest: Option<(i64, BlockPos)> = None;
+    for chunk_x in chunks(target.0) {
+        for chunk_y in chunks(target.1) {
+            let index = (chunk_x, chunk_y);
+            let chunk = world.load_chunk(index);
+            for x in 0..CHUNK_SIZE {
+                for y in 0..CHUNK_SIZE {
+                    let (world_x, world_y, _) = fluid::world_pos(index, x, y, 0);
+                    if (world_x - target.0).abs() > SEARCH_RADIUS
+                        || (world_y - target.1).abs() > SEARCH_RADIUS
+                    {
+                        continue;
+                    }
+                    for z in heights(dimension) {
+                        // 포탈 안 가장 아래 칸만 셈
+                        if chunk.get(x, y, z as usize) != blocks::NETHER_PORTAL
+                            || chunk.get(x, y, z as usize - 1) == blocks::NETHER_PORTAL
+                        {
+                            continue;
+                        }
+                        let pos = (world_x, world_y, z);
+                        let distance = [pos.0 - target.0, pos.1 - target.1, pos.2 - target.2]
+                            .iter()
+                            .map(|&d| (d as i64).pow(2))
+                            .sum();
+                        if nearest.is_none_or(|(best, _)| distance < best) {
+                            nearest = Some((distance, pos));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    nearest.map(|(_, pos)| pos)
+}
+
+/// A new portal on the ground below or around `target`, with a platform in front of
+/// and behind it and room to step out.
+fn build(
+    world: &World,
+    dimension: Dimension,
+    target: BlockPos,
+) -> (BlockPos, Vec<(BlockPos, Cube)>) {
+    let fits = |z: i32| {
+        let below = world.cube_at((target.0, target.1, z - 1));
+        below.is_some_and(|cube| cube.is_solid() && cube != blocks::LAVA)
+            && (0..3).all(|up| world.cube_at((target.0, target.1, z + up)) == Some(Cube::EMPTY))
+    };
+    let mut heights = heights(dimension);
+    let ground = match dimension {
+        // 지상은 위에서부터, 네더는 용암 위에서부터 찾음
+        Dimension::Overworld => heights.rev().find(|&z| fits(z)),
+        Dimension::Nether => heights.find(|&z| fits(z)),
+    };
+    let z = ground.unwrap_or(match dimension {
+        Dimension::Overworld => WATER_LEVEL as i32 + 1,
+        Dimension::Nether => nether::LAVA_LEVEL as i32 + 8,
+    });
+
+    let mut cubes = Vec::new();
+    for x in target.0 - 1..target.0 + 3 {
+        for y in target.1 - 1..=target.1 + 1 {
+            cubes.push(((x, y, z - 1), blocks::OBSIDIAN));
+            if y != target.1 {
+                cubes.extend((z..z + 3).map(|z| ((x, y, z), Cube::EMPTY)));
+            }
+        }
+        for up in 0..4 {
+            let frame = x == target.0 - 1 || x == target.0 + 2 || up == 3;
+            let cube = if frame {
+                blocks::OBSIDIAN
+            } else {
+                blocks::NETHER_PORTAL
+            };
+            cubes.push(((x, target.1, z + up), cube));
+        }
+    }
+    ((target.0, target.1, z), cubes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::Worlds;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("portal-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Above the Nether ceiling there is nothing but air.
+    const SKY: i32 = nether::CEILING as i32 + 20;
+
+    /// Obsidian around a `width` x `height` inside along x, with a gap at the
+    /// bottom of its right side.
+    fn frame(world: &World, width: i32, height: i32) -> Vec<BlockPos> {
+        let mut placed = Vec::new();
+        for along in -1..=width {
+            for up in -1..=height {
+                let border = along == -1 || along == width || up == -1 || up == height;
+                if border && (along, up) != (width, 0) {
+                    placed.push((along, 0, SKY + up));
+                }
+            }
+        }
+        world.set_cubes(
+            &placed
+                .iter()
+                .map(|&pos| (pos, blocks::OBSIDIAN))
+                .collect::<Vec<_>>(),
+        );
+        placed
+    }
+
+    #[test]
+    fn closing_a_frame_lights_it_and_breaking_it_puts_it_out() {
+        let dir = temp_dir("frame");
+        let worlds = Worlds::open(&dir, 42).unwrap();
+        let world = worlds.get(Dimension::Nether);
+        let placed = frame(world, 2, 3);
+        for &pos in &placed {
+            assert!(light(world, pos).is_empty(), "open frame was lit");
+        }
+
+        let gap = (2, 0, SKY);
+        world.set_cubes(&[(gap, blocks::OBSIDIAN)]);
+        let portal = light(world, gap);
+        assert_eq!(portal.len(), 6);
+        world.set_cubes(&portal);
+
+        world.set_cubes(&[((-1, 0, SKY + 1), Cube::EMPTY)]);
+        let out = extinguish(world, (-1, 0, SKY + 1));
+        assert_eq!(out.len(), 6);
+        assert!(out.iter().all(|&(_, cube)| cube == Cube::EMPTY));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn frames_must_be_large_enough_and_empty() {
+        let dir = temp_dir("small");
+        let worlds = Worlds::open(&dir, 42).unwrap();
+        let world = worlds.get(Dimension::Nether);
+        frame(world, 1, 3);
+        let gap = (1, 0, SKY);
+        world.set_cubes(&[(gap, blocks::OBSIDIAN)]);
+        assert!(light(world, gap).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+
+        let dir = temp_dir("blocked");
+        let worlds = Worlds::open(&dir, 42).unwrap();
+        let world = worlds.get(Dimension::Nether);
+        frame(world, 3, 4);
+        world.set_cubes(&[((1, 0, SKY + 2), blocks::NETHERRACK)]);
+        let gap = (3, 0, SKY);
+        world.set_cubes(&[(gap, blocks::OBSIDIAN)]);
+        assert!(light(world, gap).is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn frame_search_never_loads_chunks() {
+        let dir = temp_dir("unloaded");
+        let worlds = Worlds::open(&dir, 42).unwrap();
+        let world = worlds.get(Dimension::Nether);
+        let corner = (0, 0, SKY);
+        world.set_cubes(&[(corner, blocks::OBSIDIAN)]);
+        assert!(light(world, corner).is_empty());
+        assert!(extinguish(world, corner).is_empty());
+        for index in [(-1, 0), (0, -1), (-1, -1)] {
+            assert!(
+                world.loaded_chunk(index).is_none(),
+                "{:?} was loaded",
+                index
+            );
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn travellers_arrive_in_the_same_portal_each_time() {
+        let dir = temp_dir("arrival");
+        let worlds = Worlds::open(&dir, 42).unwrap();
+        let world = worlds.get(Dimension::Nether);
+        let (dimension, target) = destination(Dimension::Overworld, (803, -402, 90));
+        assert_eq!((dimension, target), (Dimension::Nether, (100, -51, 90)));
+
+        let (arrived, built) = arrival(world, dimension, target);
+        world.set_cubes(&built);
+        assert_eq!(world.cube_at(arrived), Some(blocks::NETHER_PORTAL));
+        assert!(heights(dimension).contains(&arrived.2));
+
+        let (again, cubes) = arrival(world, dimension, (target.0 + 5, target.1 - 3, 40));
+        assert!(cubes.is_empty());
+        assert_eq!(again.2, arrived.2);
+        assert!(built.contains(&(again, blocks::NETHER_PORTAL)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use messages::codec::FrameError;
+use messages::{DisconnectReason, PROTOCOL_VERSION};
+
+/// Anything a client can do wrong on the wire. Only that client is dropped.
+#[derive(Debug)]
+pub enum ProtocolError {
+    Frame(FrameError),
+    ExpectedHello,
+    UnexpectedHello,
+    IncompatibleProtocol { client_version: u32 },
+    BlockDefinitionsMismatch,
+    InvalidBlockPosition { x: u32, y: u32, z: u32 },
+    UnknownBlock { id: u16 },
+}
+
+impl ProtocolError {
+    /// What the client is told before the connection is closed.
+    pub fn reason(&self) -> DisconnectReason {
+        match self {
+            ProtocolError::IncompatibleProtocol { client_version } => {
+                DisconnectReason::IncompatibleProtocol {
+                    server_version: PROTOCOL_VERSION,
+                    client_version: *client_version,
+                }
+            }
+            ProtocolError::ExpectedHello => DisconnectReason::ExpectedHello,
+            ProtocolError::BlockDefinitionsMismatch => DisconnectReason::BlockDefinitionsMismatch,
+            ProtocolError::Frame(_)
+            | ProtocolError::UnexpectedHello
+            | ProtocolError::InvalidBlockPosition { .. }
+            | ProtocolError::UnknownBlock { .. } => DisconnectReason::InvalidData {
+                detail: self.to_string(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::Frame(e) => write!(f, "{}", e),
+            ProtocolError::ExpectedHello => write!(f, "first message was not Hello"),
+            ProtocolError::UnexpectedHello => write!(f, "Hello sent twice"),
+            ProtocolError::IncompatibleProtocol { client_version } => write!(
+                f,
+                "client speaks protocol {}, server speaks {}",
+                client_version, PROTOCOL_VERSION
+            ),
+            ProtocolError::BlockDefinitionsMismatch => {
+                write!(f, "client uses different block definitions")
+            }
+            ProtocolError::InvalidBlockPosition { x, y, z } => {
+                write!(
+                    f,
+                    "block position ({}, {}, {}) is outside the chunk",
+                    x, y, z
+                )
+            }
+            ProtocolError::UnknownBlock { id } => write!(f, "block id {} is not defined", id),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<FrameError> for ProtocolError {
+    fn from(e: FrameError) -> Self {
+        ProtocolError::Frame(e)
+    }
+}
@@ -0,0 +1,253 @@
+//! On-disk chunk storage.
+//!
+//! Chunks are grouped into regions of `REGION_SIZE` x `REGION_SIZE` chunks, one file
+//! per region. A file starts with a magic number and a table holding the offset and
+//! length of every chunk in the region (zero length = not stored), followed by the
+//! bincode-encoded chunks. Only chunks that differ from the generator are stored.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use map_types::Chunk;
+
+use crate::ChunkIndex;
+
+pub const REGION_SIZE: i32 = 32;
+
+const MAGIC: &[u8; 4] = b"VXR5";
+const ENTRY_SIZE: usize = 8;
+const HEADER_SIZE: usize = MAGIC.len() + (REGION_SIZE * REGION_SIZE) as usize * ENTRY_SIZE;
+
+type RegionIndex = (i32, i32);
+
+fn region_of(index: ChunkIndex) -> RegionIndex {
+    (
+        index.0.div_euclid(REGION_SIZE),
+        index.1.div_euclid(REGION_SIZE),
+    )
+}
+
+fn slot_of(index: ChunkIndex) -> usize {
+    (index.1.rem_euclid(REGION_SIZE) * REGION_SIZE + index.0.rem_euclid(REGION_SIZE)) as usize
+}
+
+fn invalid_data(detail: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, detail.into())
+}
+
+pub struct RegionStore {
+    dir: PathBuf,
+    /// Serializes file access so a load never sees a half-replaced region.
+    lock: Mutex<()>,
+}
+
+impl RegionStore {
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(RegionStore {
+            dir: dir.as_ref().to_path_buf(),
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn region_path(&self, region: RegionIndex) -> PathBuf {
+        self.dir.join(format!("r.{}.{}.region", region.0, region.1))
+    }
+
+    /// Returns the stored chunk, or `None` if it was never saved.
+    pub fn load(&self, index: ChunkIndex) -> io::Result<Option<Arc<Chunk>>> {
+        let _guard = self.lock.lock().unwrap();
+        let path = self.region_path(region_of(index));
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let table = match read_table(&bytes) {
+            Ok(table) => table,
+            Err(e) => {
+                quarantine(&path)?;
+                return Err(e);
+            }
+        };
+        let Some((offset, len)) = table[slot_of(index)] else {
+            return Ok(None);
+        };
+        bincode::deserialize(&bytes[offset..offset + len])
+            .map(|chunk| Some(Arc::new(chunk)))
+            .map_err(|e| invalid_data(e.to_string()))
+    }
+
+    /// Writes the given chunks, keeping every other chunk already stored in their regions.
+    /// Each region file is rebuilt next to the old one and renamed over it.
+    pub fn save(&self, chunks: &[(ChunkIndex, Arc<Chunk>)]) -> io::Result<()> {
+        let mut by_region: HashMap<RegionIndex, Vec<(usize, &Chunk)>> = HashMap::new();
+        for (index, chunk) in chunks {
+            by_region
+                .entry(region_of(*index))
+                .or_default()
+                .push((slot_of(*index), chunk));
+        }
+
+        let _guard = self.lock.lock().unwrap();
+        for (region, updates) in by_region {
+            let path = self.region_path(region);
+            let old = match fs::read(&path) {
+                Ok(bytes) => bytes,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+                Err(e) => return Err(e),
+            };
+
+            let mut slots: Vec<Option<&[u8]>> = vec![None; (REGION_SIZE * REGION_SIZE) as usize];
+            if !old.is_empty() {
+                match read_table(&old) {
+                    Ok(table) => {
+                        for (slot, entry) in table.into_iter().enumerate() {
+                            slots[slot] = entry.map(|(offset, len)| &old[offset..offset + len]);
+                        }
+                    }
+                    // The chunks in it are lost either way; start the region afresh.
+                    Err(_) => quarantine(&path)?,
+                }
+            }
+            let encoded: Vec<(usize, Vec<u8>)> = updates
+                .into_iter()
+                .map(|(slot, chunk)| {
+                    bincode::serialize(chunk)
+                        .map(|bytes| (slot, bytes))
+                        .map_err(|e| invalid_data(e.to_string()))
+                })
+                .collect::<io::Result<_>>()?;
+            for (slot, bytes) in &encoded {
+                slots[*slot] = Some(bytes);
+            }
+
+            let mut table = Vec::with_capacity(HEADER_SIZE);
+            let mut data = Vec::new();
+            table.extend_from_slice(MAGIC);
+            for slot in &slots {
+                let (offset, len) = match slot {
+                    Some(bytes) => {
+                        let offset = HEADER_SIZE + data.len();
+                        data.extend_from_slice(bytes);
+                        (offset as u32, bytes.len() as u32)
+                    }
+                    None => (0, 0),
+                };
+                table.extend_from_slice(&offset.to_le_bytes());
+                table.extend_from_slice(&len.to_le_bytes());
+            }
+
+            let tmp_path = path.with_extension("region.tmp");
+            let mut file = fs::File::create(&tmp_path)?;
+            file.write_all(&table)?;
+            file.write_all(&data)?;
+            file.sync_all()?;
+            fs::rename(&tmp_path, &path)?;
+        }
+        Ok(())
+    }
+}
+
+/// Moves an unreadable region file aside, so the region starts empty instead of
+/// failing every later save, and the old file is still there to inspect.
+fn quarantine(path: &Path) -> io::Result<()> {
+    let aside = path.with_extension("region.corrupt");
+    eprintln!(
+        "Region file {} is corrupt, moving it to {}",
+        path.display(),
+        aside.display()
+    );
+    fs::rename(path, aside)
+}
+
+/// Parses the offset table, checking that every entry points inside the file.
+fn read_table(bytes: &[u8]) -> io::Result<Vec<Option<(usize, usize)>>> {
+    if bytes.len() < HEADER_SIZE || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(invalid_data("not a region file"));
+    }
+    bytes[MAGIC.len()..HEADER_SIZE]
+        .chunks_exact(ENTRY_SIZE)
+        .map(|entry| {
+            let offset = u32::from_le_bytes(entry[..4].try_into().unwrap()) as usize;
+            let len = u32::from_le_bytes(entry[4..].try_into().unwrap()) as usize;
+            if len == 0 {
+                Ok(None)
+            } else if offset < HEADER_SIZE || offset + len > bytes.len() {
+                Err(invalid_data("region entry points outside the file"))
+            } else {
+                Ok(Some((offset, len)))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map_types::{blocks, Cube};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("region-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn chunk_with(cube: Cube) -> Arc<Chunk> {
+        let mut chunk = Arc::new(map_core::Map::new(1).get_chunk(0, 0));
+        Arc::make_mut(&mut chunk).set(3, 4, 5, cube);
+        chunk
+    }
+
+    fn stored_cube(store: &RegionStore, index: ChunkIndex) -> Option<Cube> {
+        store.load(index).unwrap().map(|chunk| chunk.get(3, 4, 5))
+    }
+
+    #[test]
+    fn saved_chunks_load_back_across_regions() {
+        let dir = temp_dir("roundtrip");
+        let store = RegionStore::open(&dir).unwrap();
+        let stone = blocks::STONE;
+        let bedrock = blocks::BEDROCK;
+
+        store
+            .save(&[((0, 0), chunk_with(stone)), ((-1, 40), chunk_with(bedrock))])
+            .unwrap();
+        // A later save of a neighbour must keep what is already in the region.
+        store.save(&[((31, 31), chunk_with(Cube::EMPTY))]).unwrap();
+
+        let store = RegionStore::open(&dir).unwrap();
+        assert_eq!(stored_cube(&store, (0, 0)), Some(stone));
+        assert_eq!(stored_cube(&store, (-1, 40)), Some(bedrock));
+        assert_eq!(stored_cube(&store, (31, 31)), Some(Cube::EMPTY));
+        assert_eq!(stored_cube(&store, (1, 0)), None);
+        assert_eq!(stored_cube(&store, (100, 100)), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_region_is_moved_aside() {
+        let dir = temp_dir("corrupt");
+        let store = RegionStore::open(&dir).unwrap();
+        let path = store.region_path((0, 0));
+        fs::write(&path, b"garbage").unwrap();
+
+        assert!(store.load((0, 0)).is_err());
+        assert_eq!(
+            fs::read(path.with_extension("region.corrupt")).unwrap(),
+            b"garbage"
+        );
+        assert_eq!(stored_cube(&store, (0, 0)), None);
+
+        // A save over a region that went bad since must not fail either.
+        fs::write(&path, b"garbage").unwrap();
+        store.save(&[((0, 0), chunk_with(blocks::STONE))]).unwrap();
+        assert_eq!(stored_cube(&store, (0, 0)), Some(blocks::STONE));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use map_core::Generator;
+use map_types::{Chunk, Cube, Dimension, MAP_HEIGHT};
+
+use crate::fluid::{self, BlockPos, Fluids};
+use crate::region::RegionStore;
+use crate::ChunkIndex;
+
+/// Cubes that changed, grouped by chunk, with positions inside the chunk.
+pub type ChunkChanges = BTreeMap<ChunkIndex, Vec<(usize, usize, usize, Cube)>>;
+
+/// The world of every dimension, sharing one seed.
+pub struct Worlds {
+    overworld: Arc<World>,
+    nether: Arc<World>,
+}
+
+impl Worlds {
+    /// Overworld regions are stored in `dir` itself, those of other dimensions in
+    /// subdirectories named after them.
+    pub fn open(dir: impl AsRef<Path>, seed: u64) -> io::Result<Worlds> {
+        let dir = dir.as_ref();
+        let open = |dimension: Dimension, dir: &Path| -> io::Result<Arc<World>> {
+            let store = RegionStore::open(dir)?;
+            Ok(Arc::new(World::new(Generator::new(dimension, seed), store)))
+        };
+        Ok(Worlds {
+            overworld: open(Dimension::Overworld, dir)?,
+            nether: open(Dimension::Nether, &dir.join("nether"))?,
+        })
+    }
+
+    pub fn get(&self, dimension: Dimension) -> &Arc<World> {
+        match dimension {
+            Dimension::Overworld => &self.overworld,
+            Dimension::Nether => &self.nether,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Dimension, &Arc<World>)> {
+        Dimension::ALL
+            .iter()
+            .map(|&dimension| (dimension, self.get(dimension)))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.overworld.seed()
+    }
+}
+
+/// The authoritative copy of every chunk someone is watching or has modified.
+pub struct World {
+    generator: Generator,
+    store: RegionStore,
+    chunks: Mutex<HashMap<ChunkIndex, Arc<Chunk>>>,
+    /// Chunks modified since the last flush; they must stay loaded until saved.
+    dirty: Mutex<HashSet<ChunkIndex>>,
+    /// Dirty chunks nobody watches any more, dropped once they are saved.
+    orphaned: Mutex<HashSet<ChunkIndex>>,
+    fluids: Mutex<Fluids>,
+}
+
+impl World {
+    pub fn new(generator: Generator, store: RegionStore) -> Self {
+        World {
+            generator,
+            store,
+            chunks: Mutex::new(HashMap::new()),
+            dirty: Mutex::new(HashSet::new()),
+            orphaned: Mutex::new(HashSet::new()),
+            fluids: Mutex::new(Fluids::default()),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.generator.seed()
+    }
+
+    /// Returns the loaded chunk, reading it from disk or generating it first if needed.
+    /// Both are slow, so call this from a blocking task.
+    pub fn load_chunk(&self, index: ChunkIndex) -> Arc<Chunk> {
+        if let Some(chunk) = self.loaded_chunk(index) {
+            self.orphaned.lock().unwrap().remove(&index);
+            return chunk;
+        }
+        let (chunk, corrupt) = match self.store.load(index) {
+            Ok(Some(chunk)) => (chunk, false),
+            Ok(None) => (Arc::new(self.generator.get_chunk(index.0, index.1)), false),
+            Err(e) => {
+                eprintln!("Failed to load chunk {:?}, regenerating it: {}", index, e);
+                (Arc::new(self.generator.get_chunk(index.0, index.1)), true)
+            }
+        };
+        // Another watcher may have loaded it meanwhile; keep the first copy.
+        let chunk = self
+            .chunks
+            .lock()
+            .unwrap()
+            .entry(index)
+            .or_insert(chunk)
+            .clone();
+        if corrupt {
+            // The next flush writes over the unreadable copy.
+            self.dirty.lock().unwrap().insert(index);
+        }
+        self.fluids.lock().unwrap().chunk_loaded(index);
+        chunk
+    }
+
+    pub fn loaded_chunk(&self, index: ChunkIndex) -> Option<Arc<Chunk>> {
+        self.chunks.lock().unwrap().get(&index).cloned()
+    }
+
+    /// The cube at a world position, loading its chunk if needed; `None` above or
+    /// below the map.
+    pub fn cube_at(&self, pos: BlockPos) -> Option<Cube> {
+        if !(0..MAP_HEIGHT as i32).contains(&pos.2) {
+            return None;
+        }
+        let (x, y, z) = fluid::local_of(pos);
+        Some(self.load_chunk(fluid::chunk_of(pos)).get(x, y, z))
+    }
+
+    /// Like `cube_at`, but `None` if the chunk is not loaded.
+    pub fn loaded_cube_at(&self, pos: BlockPos) -> Option<Cube> {
+        if !(0..MAP_HEIGHT as i32).contains(&pos.2) {
+            return None;
+        }
+        let (x, y, z) = fluid::local_of(pos);
+        Some(self.loaded_chunk(fluid::chunk_of(pos))?.get(x, y, z))
+    }
+
+    pub fn unload_chunk(&self, index: ChunkIndex) {
+        let mut chunks = self.chunks.lock().unwrap();
+        if self.dirty.lock().unwrap().contains(&index) {
+            self.orphaned.lock().unwrap().insert(index);
+        } else {
+            chunks.remove(&index);
+        }
+    }
+
+    /// Replaces one cube and returns the previous one.
+    /// Coordinates must already be validated against the chunk dimensions.
+    pub fn set_cube(&self, index: ChunkIndex, x: usize, y: usize, z: usize, cube: Cube) -> Cube {
+        drop(self.load_chunk(index));
+        let mut chunks = self.chunks.lock().unwrap();
+        let chunk = chunks.get_mut(&index).expect("chunk was just loaded");
+        let previous = chunk.get(x, y, z);
+        if previous != cube {
+            Arc::make_mut(chunk).set(x, y, z, cube);
+            self.dirty.lock().unwrap().insert(index);
+            // The fluid tick takes the fluids before the chunks; never the other way.
+            drop(chunks);
+            self.fluids
+                .lock()
+                .unwrap()
+                .wake(fluid::world_pos(index, x, y, z));
+        }
+        previous
+    }
+
+    /// Replaces cubes given in world coordinates and returns the ones that changed.
+    pub fn set_cubes(&self, cubes: &[(BlockPos, Cube)]) -> ChunkChanges {
+        let mut by_chunk: ChunkChanges = BTreeMap::new();
+        for &(pos, cube) in cubes {
+            let index = fluid::chunk_of(pos);
+            let (x, y, z) = fluid::local_of(pos);
+            if self.set_cube(index, x, y, z, cube) != cube {
+                by_chunk.entry(index).or_default().push((x, y, z, cube));
+            }
+        }
+        by_chunk
+    }
+
+    /// Lets fluids flow one step and returns the cubes that changed, grouped by chunk.
+    pub fn tick_fluids(&self) -> ChunkChanges {
+        let mut fluids = self.fluids.lock().unwrap();
+        if fluids.is_idle() {
+            return BTreeMap::new();
+        }
+        let changes = fluids.tick(&mut self.chunks.lock().unwrap());
+
+        let mut by_chunk: ChunkChanges = BTreeMap::new();
+        for (pos, cube) in changes {
+            let (x, y, z) = fluid::local_of(pos);
+            by_chunk
+                .entry(fluid::chunk_of(pos))
+                .or_default()
+                .push((x, y, z, cube));
+        }
+        self.dirty.lock().unwrap().extend(by_chunk.keys().copied());
+        by_chunk
+    }
+
+    /// Writes every dirty chunk to disk and drops the ones nobody watches.
+    /// Blocks on file I/O, so call this from a blocking task.
+    pub fn flush(&self) -> io::Result<usize> {
+        let snapshot: Vec<(ChunkIndex, Arc<Chunk>)> = {
+            let chunks = self.chunks.lock().unwrap();
+            let mut dirty = self.dirty.lock().unwrap();
+            dirty
+                .drain()
+                .filter_map(|index| chunks.get(&index).map(|chunk| (index, chunk.clone())))
+                .collect()
+        };
+        if snapshot.is_empty() {
+            return Ok(0);
+        }
+
+        if let Err(e) = self.store.save(&snapshot) {
+            let mut dirty = self.dirty.lock().unwrap();
+            dirty.extend(snapshot.iter().map(|(index, _)| *index));
+            return Err(e);
+        }
+
+        let mut chunks = self.chunks.lock().unwrap();
+        let dirty = self.dirty.lock().unwrap();
+        // Chunks edited again while saving stay until the next flush.
+        self.orphaned.lock().unwrap().retain(|index| {
+            if dirty.contains(index) {
+                return true;
+            }
+            chunks.remove(index);
+            false
+        });
+        Ok(snapshot.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use map_types::blocks;
+    use std::fs;
+
+    #[test]
+    fn edits_survive_a_corrupt_region() {
+        let dir = std::env::temp_dir().join(format!("world-test-corrupt-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let open = || {
+            let store = RegionStore::open(&dir).unwrap();
+            World::new(Generator::new(Dimension::Overworld, 42), store)
+        };
+        let world = open();
+        fs::write(dir.join("r.0.0.region"), b"garbage").unwrap();
+
+        world.load_chunk((0, 0));
+        world.set_cube((0, 0), 3, 4, 200, blocks::STONE);
+        assert_eq!(world.flush().unwrap(), 1);
+
+        let world = open();
+        assert_eq!(world.load_chunk((0, 0)).get(3, 4, 200), blocks::STONE);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn corrupt_chunk_is_replaced_on_the_next_flush() {
+        let dir = std::env::temp_dir().join(format!("world-test-chunk-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let open = || {
+            let store = RegionStore::open(&dir).unwrap();
+            World::new(Generator::new(Dimension::Overworld, 42), store)
+        };
+        let world = open();
+        world.set_cube((0, 0), 3, 4, 200, blocks::STONE);
+        assert_eq!(world.flush().unwrap(), 1);
+
+        // The table entry of chunk (0, 0) follows the 4-byte magic number.
+        let path = dir.join("r.0.0.region");
+        let mut bytes = fs::read(&path).unwrap();
+        let offset = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+        bytes[offset..].fill(0xff);
+        fs::write(&path, bytes).unwrap();
+        assert!(RegionStore::open(&dir).unwrap().load((0, 0)).is_err());
+
+        let world = open();
+        let generated = Generator::new(Dimension::Overworld, 42).get_chunk(0, 0);
+        assert_eq!(*world.load_chunk((0, 0)), generated);
+        assert_eq!(world.flush().unwrap(), 1);
+        assert!(RegionStore::open(&dir)
+            .unwrap()
+            .load((0, 0))
+            .unwrap()
+            .is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
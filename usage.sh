@@ -1,9 +1,11 @@
 #!/bin/sh
 
 PORT=${1:-4242}
+# Only used when the world is created
+SEED=$2
 
 # Run the server in the background
-cargo run --release --bin server -- "$PORT" &
+cargo run --release --bin server -- "$PORT" world $SEED &
 SERVER_PID=$!
 
 # Run the client in the foreground